@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use crate::components::Sprite;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlayMode {
+    #[default]
+    Loop,
+    Once,
+    PingPong,
+}
+
+/// Which clip of an [`Animator`] is playing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AnimationState {
+    #[default]
+    Idle,
+    BankLeft,
+    BankRight,
+    Hit,
+    Attack,
+}
+
+/// Frames from the atlas, each shown for its own duration in seconds.
+#[derive(Debug, Clone, Default)]
+pub struct Clip {
+    frames: Vec<(Sprite, f32)>,
+    mode: PlayMode,
+}
+
+impl Clip {
+    pub const fn new(mode: PlayMode) -> Self {
+        Self {
+            frames: Vec::new(),
+            mode,
+        }
+    }
+
+    /// Single frame clip for a state that doesn't animate.
+    pub fn still(sprite: Sprite) -> Self {
+        Self::new(PlayMode::Loop).frame(sprite, 1.)
+    }
+
+    pub fn frame(mut self, sprite: Sprite, duration: f32) -> Self {
+        self.frames.push((sprite, duration));
+        self
+    }
+
+    fn order(&self) -> impl Iterator<Item = &(Sprite, f32)> {
+        let len = self.frames.len();
+        let back = match self.mode {
+            PlayMode::PingPong => 1..len.saturating_sub(1),
+            PlayMode::Loop | PlayMode::Once => 0..0,
+        };
+
+        self.frames
+            .iter()
+            .chain(back.rev().map(move |index| &self.frames[index]))
+    }
+
+    /// How long one pass take, a ping pong pass goes forward then back.
+    pub fn duration(&self) -> f32 {
+        self.order().map(|(_, duration)| duration).sum()
+    }
+
+    /// Frame shown `elapsed` seconds into the clip, `None` once a [`PlayMode::Once`] clip is over.
+    pub fn sample(&self, elapsed: f32) -> Option<Sprite> {
+        let duration = self.duration();
+        if duration <= 0. {
+            return self.frames.first().map(|(sprite, _)| *sprite);
+        }
+
+        let mut time = match self.mode {
+            PlayMode::Once if elapsed >= duration => return None,
+            PlayMode::Once => elapsed,
+            PlayMode::Loop | PlayMode::PingPong => elapsed % duration,
+        };
+
+        for (sprite, duration) in self.order() {
+            if time < *duration {
+                return Some(*sprite);
+            }
+            time -= duration;
+        }
+
+        self.frames.last().map(|(sprite, _)| *sprite)
+    }
+}
+
+/// Pick the [`Sprite`] of an entity from a set of clips, a base state that
+/// follow what the entity is doing and a one shot clip played over it.
+#[derive(Debug, Clone)]
+pub struct Animator {
+    clips: HashMap<AnimationState, Clip>,
+    state: AnimationState,
+    elapsed: f32,
+    one_shot: Option<(AnimationState, f32)>,
+}
+
+impl Animator {
+    pub fn new(idle: Clip) -> Self {
+        Self {
+            clips: HashMap::from([(AnimationState::Idle, idle)]),
+            state: AnimationState::Idle,
+            elapsed: 0.,
+            one_shot: None,
+        }
+    }
+
+    pub fn with(mut self, state: AnimationState, clip: Clip) -> Self {
+        self.clips.insert(state, clip);
+        self
+    }
+
+    pub fn has(&self, state: AnimationState) -> bool {
+        self.clips.contains_key(&state)
+    }
+
+    /// Change the base state, a state without clip fall back to idle.
+    pub fn set_state(&mut self, state: AnimationState) {
+        let state = if self.has(state) {
+            state
+        } else {
+            AnimationState::Idle
+        };
+
+        if self.state != state {
+            self.state = state;
+            self.elapsed = 0.;
+        }
+    }
+
+    /// Play the clip of `state` once on top of the base state, ignored when there is no such clip.
+    pub fn trigger(&mut self, state: AnimationState) {
+        if self.has(state) {
+            self.one_shot = Some((state, 0.));
+        }
+    }
+
+    #[cfg(feature = "hot-reload")]
+    pub fn sprites_mut(&mut self) -> impl Iterator<Item = &mut Sprite> {
+        self.clips
+            .values_mut()
+            .flat_map(|clip| clip.frames.iter_mut().map(|(sprite, _)| sprite))
+    }
+
+    pub fn update(&mut self, delta: f32) -> Option<Sprite> {
+        if let Some((state, elapsed)) = &mut self.one_shot {
+            *elapsed += delta;
+            let clip = &self.clips[state];
+            if *elapsed < clip.duration() {
+                return clip.sample(*elapsed);
+            }
+            self.one_shot = None;
+        }
+
+        self.elapsed += delta;
+        self.clips
+            .get(&self.state)
+            .and_then(|clip| clip.sample(self.elapsed))
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use macroquad::math::Rect;
+
+use crate::components::Sprite;
+
+const PLACEHOLDER: Sprite = Sprite(Rect {
+    x: 0.,
+    y: 0.,
+    w: 16.,
+    h: 16.,
+});
+
+/// Every sprite the game look up by name, an atlas missing one is rejected when loaded.
+pub const SPRITES: [&str; 17] = [
+    "player",
+    "player_bank_left",
+    "player_bank_right",
+    "red_alien",
+    "red_alien_attack",
+    "boss",
+    "boss_attack",
+    "bullet_kunai",
+    "bullet_arrow_small",
+    "bg_space",
+    "bg_stars",
+    "bg_nebula",
+    "bg_boss_grid",
+    "particle_round",
+    "particle_spark",
+    "particle_ring",
+    "item_point",
+];
+
+/// Named rectangles of the sprite atlas, loaded from a metadata file where
+/// every line is `name x y width height` in pixels and `#` start a comment.
+///
+/// The rectangle only pick the texels, the drawn size still come from the
+/// [`crate::components::Transform2D`] scale whatever the rectangle size.
+#[derive(Debug, Default)]
+pub struct SpriteAtlas {
+    sprites: HashMap<String, Sprite>,
+    fallback: Option<Sprite>,
+}
+
+impl SpriteAtlas {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut sprites = HashMap::new();
+
+        for (number, line) in source.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            let name = fields.next().unwrap_or_default();
+            let rect = fields
+                .map(str::parse::<f32>)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|err| format!("line {}: {err}", number + 1))?;
+
+            let [x, y, width, height] = rect[..] else {
+                return Err(format!(
+                    "line {}: expected `name x y width height`",
+                    number + 1
+                ));
+            };
+
+            if sprites
+                .insert(name.to_string(), Sprite::new(x, y, width, height))
+                .is_some()
+            {
+                return Err(format!("line {}: `{name}` is defined twice", number + 1));
+            }
+        }
+
+        Ok(Self {
+            sprites,
+            fallback: None,
+        })
+    }
+
+    /// Used when the atlas failed to load, every name is the whole placeholder texture.
+    pub fn placeholder() -> Self {
+        Self {
+            sprites: HashMap::new(),
+            fallback: Some(PLACEHOLDER),
+        }
+    }
+
+    /// Name of every [`SPRITES`] this atlas doesn't have.
+    pub fn missing(&self) -> impl Iterator<Item = &'static str> + '_ {
+        SPRITES
+            .into_iter()
+            .filter(|name| !self.sprites.contains_key(*name))
+    }
+
+    /// A sprite missing from the atlas is reported and drawn with the top left corner of it.
+    pub fn get(&self, name: &str) -> Sprite {
+        self.sprites
+            .get(name)
+            .copied()
+            .or(self.fallback)
+            .unwrap_or_else(|| {
+                eprintln!("asset error: sprite `{name}` is not in the atlas");
+                PLACEHOLDER
+            })
+    }
+
+    /// Same named sprite in this atlas as `sprite` was in `old`, unchanged when it has no name.
+    #[cfg(feature = "hot-reload")]
+    pub fn remap(&self, old: &Self, sprite: Sprite) -> Sprite {
+        old.sprites
+            .iter()
+            .find(|(_, old)| old.0 == sprite.0)
+            .and_then(|(name, _)| self.sprites.get(name))
+            .copied()
+            .unwrap_or(sprite)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_skip_comments_and_blank_lines() {
+        let atlas = SpriteAtlas::parse(
+            "# name x y width height\n\nplayer 0 0 16 16 # ship\n  bullet 16 0 8 4\n",
+        )
+        .unwrap();
+
+        assert_eq!(atlas.get("player").0, Rect::new(0., 0., 16., 16.));
+        assert_eq!(atlas.get("bullet").0, Rect::new(16., 0., 8., 4.));
+    }
+
+    #[test]
+    fn parse_reject_malformed_lines() {
+        for source in [
+            "player 0 0 16",
+            "player 0 0 16 16 16",
+            "player 0 zero 16 16",
+            "player",
+        ] {
+            assert!(SpriteAtlas::parse(source).is_err(), "{source}");
+        }
+
+        let err = SpriteAtlas::parse("player 0 0 16 16\nboss 0 0 sixteen 16").unwrap_err();
+        assert!(err.starts_with("line 2"), "{err}");
+    }
+
+    #[test]
+    fn parse_reject_duplicate_names() {
+        let err = SpriteAtlas::parse("player 0 0 16 16\nplayer 16 0 16 16").unwrap_err();
+        assert_eq!(err, "line 2: `player` is defined twice");
+    }
+
+    #[test]
+    fn shipped_atlas_has_every_sprite() {
+        let atlas = SpriteAtlas::parse(include_str!("../assets/atlas.txt")).unwrap();
+        assert_eq!(atlas.missing().collect::<Vec<_>>(), Vec::<&str>::new());
+
+        let atlas = SpriteAtlas::parse("player 0 0 16 16").unwrap();
+        assert_eq!(atlas.missing().count(), SPRITES.len() - 1);
+    }
+
+    #[test]
+    fn missing_sprite_fall_back_instead_of_panicking() {
+        let atlas = SpriteAtlas::parse("player 32 0 16 16").unwrap();
+
+        assert_eq!(atlas.get("boss").0, PLACEHOLDER.0);
+        assert_eq!(SpriteAtlas::placeholder().get("boss").0, PLACEHOLDER.0);
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::VecDeque;
+
+pub trait AudioBackend {
+    /// `pan` goes from -1 on the left to 1 on the right.
+    fn play(&mut self, sound: &str, volume: f32, pan: f32);
+
+    /// Start a music section, a looped one keep playing until stopped.
+    fn play_music(&mut self, sound: &str, volume: f32, looped: bool);
+
+    fn set_volume(&mut self, sound: &str, volume: f32);
+
+    fn stop(&mut self, sound: &str);
+}
+
+// INFO : quad-snd can't pan, every sound effect is loaded once per position
+// with its sides scaled in quarter and the closest one is played
+#[cfg(feature = "audio")]
+const PAN_POSITIONS: [(f32, [i32; 2]); 5] = [
+    (-1., [4, 0]),
+    (-0.5, [4, 2]),
+    (0., [4, 4]),
+    (0.5, [2, 4]),
+    (1., [0, 4]),
+];
+
+/// Play with macroquad, only with the `audio` feature since it link to the
+/// system audio library.
+#[cfg(feature = "audio")]
+pub struct MacroquadAudio {
+    sounds: std::collections::HashMap<String, Vec<(f32, macroquad::audio::Sound)>>,
+}
+
+#[cfg(feature = "audio")]
+impl MacroquadAudio {
+    /// Every `(name, wav)` pair that macroquad could decode, `sounds` are
+    /// panned while `music` sections are kept as they are.
+    pub async fn load<'a>(
+        sounds: impl IntoIterator<Item = (String, &'a [u8])>,
+        music: impl IntoIterator<Item = (String, &'a [u8])>,
+    ) -> Self {
+        let mut loaded = std::collections::HashMap::new();
+        for (name, bytes) in sounds {
+            let wav = super::Wav::parse(bytes);
+            let mut positions = Vec::new();
+            for (pan, gains) in PAN_POSITIONS {
+                // INFO : Formats that can't be panned play the same on both side
+                let Some(panned) = wav.as_ref().and_then(|wav| wav.panned(gains)) else {
+                    positions.extend(decode(&name, bytes).await.map(|sound| (0., sound)));
+                    break;
+                };
+                positions.extend(decode(&name, &panned).await.map(|sound| (pan, sound)));
+            }
+            loaded.insert(name, positions);
+        }
+
+        for (name, bytes) in music {
+            let section = decode(&name, bytes).await.map(|sound| (0., sound));
+            loaded.insert(name, section.into_iter().collect());
+        }
+
+        Self { sounds: loaded }
+    }
+
+    fn each(&self, sound: &str) -> impl Iterator<Item = &macroquad::audio::Sound> {
+        self.sounds
+            .get(sound)
+            .into_iter()
+            .flatten()
+            .map(|(_, sound)| sound)
+    }
+}
+
+#[cfg(feature = "audio")]
+async fn decode(name: &str, bytes: &[u8]) -> Option<macroquad::audio::Sound> {
+    macroquad::audio::load_sound_from_bytes(bytes)
+        .await
+        .map_err(|err| eprintln!("sound `{name}`: {err}"))
+        .ok()
+}
+
+#[cfg(feature = "audio")]
+impl AudioBackend for MacroquadAudio {
+    fn play(&mut self, sound: &str, volume: f32, pan: f32) {
+        let closest = self.sounds.get(sound).and_then(|positions| {
+            positions
+                .iter()
+                .min_by(|a, b| (a.0 - pan).abs().total_cmp(&(b.0 - pan).abs()))
+        });
+
+        if let Some((_, sound)) = closest {
+            macroquad::audio::play_sound(
+                sound,
+                macroquad::audio::PlaySoundParams {
+                    looped: false,
+                    volume,
+                },
+            );
+        }
+    }
+
+    fn play_music(&mut self, sound: &str, volume: f32, looped: bool) {
+        if let Some(sound) = self.each(sound).next() {
+            macroquad::audio::play_sound(
+                sound,
+                macroquad::audio::PlaySoundParams { looped, volume },
+            );
+        }
+    }
+
+    fn set_volume(&mut self, sound: &str, volume: f32) {
+        for sound in self.each(sound) {
+            macroquad::audio::set_sound_volume(sound, volume);
+        }
+    }
+
+    fn stop(&mut self, sound: &str) {
+        for sound in self.each(sound) {
+            macroquad::audio::stop_sound(sound);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AudioEvent {
+    Play {
+        sound: String,
+        volume: f32,
+        pan: f32,
+    },
+    Music {
+        sound: String,
+        volume: f32,
+        looped: bool,
+    },
+    Volume {
+        sound: String,
+        volume: f32,
+    },
+    Stop {
+        sound: String,
+    },
+}
+
+/// Keep the last calls instead of playing anything, used by tests and when
+/// there is no way to play sounds.
+#[derive(Default)]
+pub struct RecordingBackend {
+    pub events: VecDeque<AudioEvent>,
+}
+
+impl RecordingBackend {
+    const HISTORY: usize = 256;
+
+    fn record(&mut self, event: AudioEvent) {
+        if self.events.len() == Self::HISTORY {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+}
+
+impl AudioBackend for RecordingBackend {
+    fn play(&mut self, sound: &str, volume: f32, pan: f32) {
+        self.record(AudioEvent::Play {
+            sound: sound.to_string(),
+            volume,
+            pan,
+        });
+    }
+
+    fn play_music(&mut self, sound: &str, volume: f32, looped: bool) {
+        self.record(AudioEvent::Music {
+            sound: sound.to_string(),
+            volume,
+            looped,
+        });
+    }
+
+    fn set_volume(&mut self, sound: &str, volume: f32) {
+        self.record(AudioEvent::Volume {
+            sound: sound.to_string(),
+            volume,
+        });
+    }
+
+    fn stop(&mut self, sound: &str) {
+        self.record(AudioEvent::Stop {
+            sound: sound.to_string(),
+        });
+    }
+}
@@ -0,0 +1,86 @@
+use crate::cue::{Cue, Cues};
+
+mod backend;
+mod music;
+mod sfx;
+mod wav;
+
+#[cfg(feature = "audio")]
+use backend::MacroquadAudio;
+pub use backend::{AudioBackend, RecordingBackend};
+pub use music::{parse_tracks, Music, TrackData, TrackEntry};
+pub use sfx::{parse_sounds, Sfx, SoundData};
+pub use wav::Wav;
+
+/// Play with macroquad, with `--mute` sounds are only recorded.
+#[cfg(feature = "audio")]
+pub async fn backend(sounds: &[SoundData], tracks: &[TrackData]) -> Box<dyn AudioBackend> {
+    if std::env::args().any(|arg| arg == "--mute") {
+        return Box::new(RecordingBackend::default());
+    }
+
+    let sounds = sounds
+        .iter()
+        .map(|sound| (sound.name.clone(), sound.bytes.as_slice()));
+    let sections = tracks.iter().flat_map(|track| {
+        [
+            (track.intro_name(), track.sections.0.as_slice()),
+            (track.loop_name(), track.sections.1.as_slice()),
+        ]
+    });
+
+    Box::new(MacroquadAudio::load(sounds, sections).await)
+}
+
+/// Sound effects and music sharing one backend.
+pub struct Audio {
+    backend: Box<dyn AudioBackend>,
+    pub sfx: Sfx,
+    pub music: Music,
+}
+
+impl Audio {
+    pub fn new(backend: Box<dyn AudioBackend>, sounds: &[SoundData], tracks: &[TrackData]) -> Self {
+        Self {
+            backend,
+            sfx: Sfx::new(sounds),
+            music: Music::new(tracks),
+        }
+    }
+
+    pub fn play_cues(&mut self, cues: &Cues) {
+        for (cue, x) in cues.iter() {
+            self.sfx.play(self.backend.as_mut(), sound_of(cue), x);
+        }
+    }
+
+    pub fn play_music(&mut self, track: &str, fade: f32) {
+        self.music.play(self.backend.as_mut(), track, fade);
+    }
+
+    pub fn stop_music(&mut self, fade: f32) {
+        self.music.stop(self.backend.as_mut(), fade);
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.music.set_paused(self.backend.as_mut(), paused);
+    }
+
+    pub fn update(&mut self, delta: f32) {
+        self.sfx.update(delta);
+        self.music.update(self.backend.as_mut(), delta);
+    }
+}
+
+const fn sound_of(cue: Cue) -> &'static str {
+    match cue {
+        Cue::PlayerShot => "shot",
+        Cue::EnemyHit => "hit",
+        Cue::EnemyDeath => "enemy_death",
+        Cue::Graze => "graze",
+        Cue::Pickup => "pickup",
+        Cue::Bomb => "bomb",
+        Cue::PlayerDeath => "player_death",
+        Cue::BossLowHp => "boss_low_hp",
+    }
+}
@@ -0,0 +1,362 @@
+use std::collections::HashMap;
+
+use super::AudioBackend;
+
+/// One line of the music metadata file, `name file loop_start loop_end title`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackEntry {
+    pub name: String,
+    pub file: String,
+    pub loop_start: f64,
+    pub loop_end: f64,
+    pub title: String,
+}
+
+pub fn parse_tracks(source: &str) -> Result<Vec<TrackEntry>, String> {
+    let mut entries = Vec::<TrackEntry>::new();
+
+    for (number, line) in source.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut fields = line.split_whitespace();
+        let (Some(name), Some(file), Some(loop_start), Some(loop_end)) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            return Err(format!(
+                "line {}: expected `name file loop_start loop_end title`",
+                number + 1
+            ));
+        };
+
+        let loop_start = loop_start
+            .parse::<f64>()
+            .map_err(|err| format!("line {}: {err}", number + 1))?;
+        let loop_end = loop_end
+            .parse::<f64>()
+            .map_err(|err| format!("line {}: {err}", number + 1))?;
+        if loop_start < 0. || loop_end <= loop_start {
+            return Err(format!(
+                "line {}: the loop has to end after it start",
+                number + 1
+            ));
+        }
+
+        if entries.iter().any(|entry| entry.name == name) {
+            return Err(format!("line {}: `{name}` is defined twice", number + 1));
+        }
+
+        entries.push(TrackEntry {
+            name: name.to_string(),
+            file: file.to_string(),
+            loop_start,
+            loop_end,
+            title: fields.collect::<Vec<_>>().join(" "),
+        });
+    }
+
+    Ok(entries)
+}
+
+/// A track cut in its intro and loop sections, loaded by [`crate::resources::Resources`].
+#[derive(Debug, Clone)]
+pub struct TrackData {
+    pub name: String,
+    pub title: String,
+    /// Length of the intro in seconds, 0 when the track loop from the start.
+    pub intro: f64,
+    #[cfg(feature = "audio")]
+    pub sections: (Vec<u8>, Vec<u8>),
+}
+
+#[cfg(feature = "audio")]
+impl TrackData {
+    pub fn intro_name(&self) -> String {
+        intro_name(&self.name)
+    }
+
+    pub fn loop_name(&self) -> String {
+        loop_name(&self.name)
+    }
+}
+
+fn intro_name(track: &str) -> String {
+    format!("{track}:intro")
+}
+
+fn loop_name(track: &str) -> String {
+    format!("{track}:loop")
+}
+
+#[derive(Debug, Clone)]
+struct Channel {
+    track: String,
+    /// Seconds left of the intro, the loop section start when it reach 0.
+    intro: f64,
+    gain: f32,
+    /// Gain per second, negative when fading out.
+    fade: f32,
+    applied: f32,
+}
+
+impl Channel {
+    fn section(&self) -> String {
+        if self.intro > 0. {
+            intro_name(&self.track)
+        } else {
+            loop_name(&self.track)
+        }
+    }
+}
+
+/// Play one track at a time, the previous one fade out while the next fade in.
+pub struct Music {
+    tracks: HashMap<String, (String, f64)>,
+    current: Option<Channel>,
+    fading: Vec<Channel>,
+    since_change: f32,
+    paused: bool,
+    pub volume: f32,
+}
+
+impl Music {
+    pub fn new(tracks: &[TrackData]) -> Self {
+        Self {
+            tracks: tracks
+                .iter()
+                .map(|track| (track.name.clone(), (track.title.clone(), track.intro)))
+                .collect(),
+            current: None,
+            fading: Vec::new(),
+            since_change: 0.,
+            paused: false,
+            volume: 1.,
+        }
+    }
+
+    /// Crossfade to `track` over `fade` seconds, nothing happen if it is already playing.
+    pub fn play(&mut self, backend: &mut dyn AudioBackend, track: &str, fade: f32) {
+        let Some((_, intro)) = self.tracks.get(track) else {
+            return;
+        };
+        if self
+            .current
+            .as_ref()
+            .is_some_and(|current| current.track == track)
+        {
+            return;
+        }
+
+        let channel = Channel {
+            track: track.to_string(),
+            intro: *intro,
+            gain: if fade > 0. { 0. } else { 1. },
+            fade: if fade > 0. { fade.recip() } else { 0. },
+            applied: 0.,
+        };
+
+        self.stop(backend, fade);
+        // INFO : Sections are addressed by name, an old channel of the track would duck and stop the new one
+        self.fading.retain(|fading| {
+            let same = fading.track == track;
+            if same {
+                backend.stop(&fading.section());
+            }
+            !same
+        });
+        self.start(backend, channel);
+        self.since_change = 0.;
+    }
+
+    pub fn stop(&mut self, backend: &mut dyn AudioBackend, fade: f32) {
+        let Some(mut channel) = self.current.take() else {
+            return;
+        };
+
+        if fade > 0. {
+            channel.fade = -fade.recip();
+            self.fading.push(channel);
+        } else {
+            backend.stop(&channel.section());
+        }
+    }
+
+    fn start(&mut self, backend: &mut dyn AudioBackend, mut channel: Channel) {
+        channel.applied = channel.gain * self.volume;
+        backend.play_music(&channel.section(), channel.applied, channel.intro <= 0.);
+        self.current = Some(channel);
+    }
+
+    // INFO : Macroquad can't pause a sound, the track is stopped and start over from its loop
+    pub fn set_paused(&mut self, backend: &mut dyn AudioBackend, paused: bool) {
+        if paused == self.paused {
+            return;
+        }
+        self.paused = paused;
+
+        for channel in self.fading.drain(..) {
+            backend.stop(&channel.section());
+        }
+
+        if let Some(mut channel) = self.current.take() {
+            if paused {
+                backend.stop(&channel.section());
+                self.current = Some(channel);
+            } else {
+                channel.intro = 0.;
+                self.start(backend, channel);
+            }
+        }
+    }
+
+    pub fn update(&mut self, backend: &mut dyn AudioBackend, delta: f32) {
+        if self.paused {
+            return;
+        }
+        self.since_change += delta;
+
+        for channel in self.current.iter_mut().chain(&mut self.fading) {
+            channel.gain = channel.fade.mul_add(delta, channel.gain).clamp(0., 1.);
+
+            if channel.intro > 0. {
+                channel.intro -= f64::from(delta);
+                if channel.intro <= 0. {
+                    backend.play_music(&loop_name(&channel.track), channel.applied, true);
+                }
+            }
+
+            let volume = channel.gain * self.volume;
+            if (volume - channel.applied).abs() > f32::EPSILON {
+                backend.set_volume(&channel.section(), volume);
+                channel.applied = volume;
+            }
+        }
+
+        self.fading.retain(|channel| {
+            let done = channel.gain <= 0.;
+            if done {
+                backend.stop(&channel.section());
+            }
+            !done
+        });
+    }
+
+    /// Title of the current track and how many seconds ago it started.
+    pub fn now_playing(&self) -> Option<(&str, f32)> {
+        let current = self.current.as_ref()?;
+        self.tracks
+            .get(&current.track)
+            .map(|(title, _)| (title.as_str(), self.since_change))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_tracks, Music, TrackData};
+    use crate::audio::backend::{AudioEvent, RecordingBackend};
+
+    fn track(name: &str) -> TrackData {
+        TrackData {
+            name: name.to_string(),
+            title: name.to_string(),
+            intro: 0.,
+            #[cfg(feature = "audio")]
+            sections: (Vec::new(), Vec::new()),
+        }
+    }
+
+    #[test]
+    fn replaying_a_fading_track_is_not_cut() {
+        let mut music = Music::new(&[track("stage_1"), track("boss_1")]);
+        let mut backend = RecordingBackend::default();
+
+        music.play(&mut backend, "stage_1", 0.);
+        music.play(&mut backend, "boss_1", 2.);
+        music.update(&mut backend, 0.5);
+        // INFO : Restarting the stage while stage_1 is still fading out
+        music.play(&mut backend, "stage_1", 0.);
+        backend.events.clear();
+        music.update(&mut backend, 3.);
+
+        assert!(!backend.events.iter().any(|event| matches!(
+            event,
+            AudioEvent::Stop { sound } | AudioEvent::Volume { sound, .. }
+                if sound.starts_with("stage_1")
+        )));
+        assert_eq!(music.now_playing().map(|(title, _)| title), Some("stage_1"));
+    }
+
+    #[test]
+    fn parse_read_loop_points_and_title() {
+        let tracks =
+            parse_tracks("# comment\n\nstage_1 music/stage_1.wav 4 12 Into the Void\n").unwrap();
+
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(tracks[0].name, "stage_1");
+        assert_eq!(tracks[0].file, "music/stage_1.wav");
+        assert!((tracks[0].loop_start - 4.).abs() < f64::EPSILON);
+        assert!((tracks[0].loop_end - 12.).abs() < f64::EPSILON);
+        assert_eq!(tracks[0].title, "Into the Void");
+    }
+
+    #[test]
+    fn parse_reject_missing_fields() {
+        for source in [
+            "stage_1",
+            "stage_1 music/stage_1.wav",
+            "stage_1 music/stage_1.wav 4",
+        ] {
+            let err = parse_tracks(source).unwrap_err();
+            assert!(
+                err.contains("expected `name file loop_start loop_end title`"),
+                "{err}"
+            );
+        }
+        assert!(parse_tracks("stage_1 music/stage_1.wav four 12").is_err());
+    }
+
+    #[test]
+    fn parse_reject_loop_ending_before_it_start() {
+        for source in ["a a.wav 4 4", "a a.wav 4 2", "a a.wav -1 2"] {
+            assert_eq!(
+                parse_tracks(source).unwrap_err(),
+                "line 1: the loop has to end after it start"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_reject_duplicate_names() {
+        assert_eq!(
+            parse_tracks("a a.wav 0 2\nb b.wav 0 2\na c.wav 0 2").unwrap_err(),
+            "line 3: `a` is defined twice"
+        );
+    }
+
+    #[test]
+    fn intro_hand_off_to_the_loop() {
+        let mut music = Music::new(&[TrackData {
+            intro: 1.,
+            ..track("stage_1")
+        }]);
+        let mut backend = RecordingBackend::default();
+
+        music.play(&mut backend, "stage_1", 0.);
+        assert!(matches!(
+            backend.events.make_contiguous(),
+            [AudioEvent::Music { sound, looped: false, .. }] if sound == "stage_1:intro"
+        ));
+
+        backend.events.clear();
+        music.update(&mut backend, 0.6);
+        assert!(backend.events.is_empty());
+
+        music.update(&mut backend, 0.6);
+        assert!(matches!(
+            backend.events.make_contiguous(),
+            [AudioEvent::Music { sound, looped: true, .. }] if sound == "stage_1:loop"
+        ));
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use super::AudioBackend;
+
+/// One line of the sound metadata file, `name file volume max_instances`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoundEntry {
+    pub name: String,
+    pub file: String,
+    pub volume: f32,
+    pub instances: usize,
+}
+
+pub fn parse_sounds(source: &str) -> Result<Vec<SoundEntry>, String> {
+    let mut entries = Vec::<SoundEntry>::new();
+
+    for (number, line) in source.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let [name, file, volume, instances] = line.split_whitespace().collect::<Vec<_>>()[..]
+        else {
+            return Err(format!(
+                "line {}: expected `name file volume max_instances`",
+                number + 1
+            ));
+        };
+
+        let volume = volume
+            .parse::<f32>()
+            .map_err(|err| format!("line {}: {err}", number + 1))?;
+        let instances = instances
+            .parse::<usize>()
+            .map_err(|err| format!("line {}: {err}", number + 1))?;
+
+        if entries.iter().any(|entry| entry.name == name) {
+            return Err(format!("line {}: `{name}` is defined twice", number + 1));
+        }
+
+        entries.push(SoundEntry {
+            name: name.to_string(),
+            file: file.to_string(),
+            volume: volume.clamp(0., 1.),
+            instances: instances.max(1),
+        });
+    }
+
+    Ok(entries)
+}
+
+/// A sound effect with its file already read, loaded by [`crate::resources::Resources`].
+#[derive(Debug, Clone)]
+pub struct SoundData {
+    pub name: String,
+    // INFO : Only kept when there is something to play them with
+    #[cfg(feature = "audio")]
+    pub bytes: Vec<u8>,
+    pub volume: f32,
+    pub instances: usize,
+    pub duration: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sound {
+    volume: f32,
+    instances: usize,
+    duration: f64,
+}
+
+/// Sound effects, play them by name with where they happened on the stage,
+/// sounds already playing `max_instances` times are skipped.
+pub struct Sfx {
+    sounds: HashMap<String, Sound>,
+    playing: Vec<(String, f64)>,
+    pub volume: f32,
+}
+
+impl Sfx {
+    pub fn new(sounds: &[SoundData]) -> Self {
+        Self {
+            sounds: sounds
+                .iter()
+                .map(|sound| {
+                    (
+                        sound.name.clone(),
+                        Sound {
+                            volume: sound.volume,
+                            instances: sound.instances,
+                            duration: sound.duration,
+                        },
+                    )
+                })
+                .collect(),
+            playing: Vec::new(),
+            volume: 1.,
+        }
+    }
+
+    /// `x` is in stage unit, the left edge pan fully left and the right edge fully right.
+    pub fn play(&mut self, backend: &mut dyn AudioBackend, name: &str, x: f32) {
+        let Some(sound) = self.sounds.get(name) else {
+            return;
+        };
+
+        let instances = self.playing.iter().filter(|(n, _)| n == name).count();
+        if instances >= sound.instances {
+            return;
+        }
+
+        let pan = x.mul_add(2., -1.).clamp(-1., 1.);
+        backend.play(name, sound.volume * self.volume, pan);
+        self.playing.push((name.to_string(), sound.duration));
+    }
+
+    pub fn update(&mut self, delta: f32) {
+        for (_, remaining) in &mut self.playing {
+            *remaining -= f64::from(delta);
+        }
+        self.playing.retain(|(_, remaining)| *remaining > 0.);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Sfx, SoundData};
+    use crate::audio::backend::{AudioEvent, RecordingBackend};
+
+    fn sfx() -> Sfx {
+        Sfx::new(&[SoundData {
+            name: String::from("shot"),
+            #[cfg(feature = "audio")]
+            bytes: Vec::new(),
+            volume: 0.5,
+            instances: 2,
+            duration: 0.1,
+        }])
+    }
+
+    #[test]
+    fn instances_are_limited_until_one_end() {
+        let mut sfx = sfx();
+        let mut backend = RecordingBackend::default();
+
+        for _ in 0..3 {
+            sfx.play(&mut backend, "shot", 0.5);
+        }
+        assert_eq!(backend.events.len(), 2);
+
+        sfx.update(0.2);
+        sfx.play(&mut backend, "shot", 0.5);
+        assert_eq!(backend.events.len(), 3);
+    }
+
+    #[test]
+    fn pan_follow_x_from_left_to_right() {
+        let mut backend = RecordingBackend::default();
+        for x in [-0.5, 0., 0.25, 0.5, 1., 1.5] {
+            sfx().play(&mut backend, "shot", x);
+        }
+
+        let pans = backend
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                AudioEvent::Play { pan, .. } => Some(*pan),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(pans, [-1., -1., -0.5, 0., 1., 1.]);
+    }
+}
@@ -0,0 +1,135 @@
+/// Just enough of a PCM wav file to know its length and cut it in sections.
+pub struct Wav<'a> {
+    format: &'a [u8],
+    data: &'a [u8],
+}
+
+impl<'a> Wav<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Option<Self> {
+        if bytes.get(0..4)? != b"RIFF" || bytes.get(8..12)? != b"WAVE" {
+            return None;
+        }
+
+        let mut format = None;
+        let mut at = 12;
+        while let (Some(id), Some(size)) = (bytes.get(at..at + 4), u32_at(bytes, at + 4)) {
+            let size = usize::try_from(size).ok()?;
+            let chunk = bytes.get(at + 8..at + 8 + size)?;
+            match id {
+                b"fmt " => format = Some(chunk),
+                b"data" => {
+                    return Some(Self {
+                        format: format?,
+                        data: chunk,
+                    })
+                }
+                _ => {}
+            }
+            // INFO : Chunks are padded to an even size
+            at += 8 + size + size % 2;
+        }
+
+        None
+    }
+
+    fn byte_rate(&self) -> u32 {
+        u32_at(self.format, 8).unwrap_or(1).max(1)
+    }
+
+    #[cfg(feature = "audio")]
+    fn block_align(&self) -> usize {
+        self.format
+            .get(12..14)
+            .map_or(1, |b| usize::from(u16::from_le_bytes([b[0], b[1]])).max(1))
+    }
+
+    /// Length in seconds.
+    pub fn duration(&self) -> f64 {
+        self.seconds(self.data.len())
+    }
+
+    fn seconds(&self, bytes: usize) -> f64 {
+        u32::try_from(bytes).map_or(f64::MAX, f64::from) / f64::from(self.byte_rate())
+    }
+
+    /// Byte offset of the sample frame at `seconds`, clamped to the data.
+    #[cfg(feature = "audio")]
+    fn offset(&self, seconds: f64) -> usize {
+        let frames = (0..self.data.len() / self.block_align())
+            .take_while(|frame| self.seconds(frame * self.block_align()) < seconds)
+            .count();
+        (frames * self.block_align()).min(self.data.len())
+    }
+
+    /// A new 16 bit stereo wav file with the left and right side scaled by
+    /// `gains` in quarter, `None` when the samples aren't 16 bit PCM.
+    #[cfg(feature = "audio")]
+    pub fn panned(&self, gains: [i32; 2]) -> Option<Vec<u8>> {
+        let channels = usize::from(u16_at(self.format, 2)?);
+        if u16_at(self.format, 0)? != 1 || u16_at(self.format, 14)? != 16 || channels > 2 {
+            return None;
+        }
+        let sample_rate = u32_at(self.format, 4)?;
+
+        let mut format = Vec::with_capacity(16);
+        format.extend_from_slice(&1_u16.to_le_bytes());
+        format.extend_from_slice(&2_u16.to_le_bytes());
+        format.extend_from_slice(&sample_rate.to_le_bytes());
+        format.extend_from_slice(&sample_rate.checked_mul(4)?.to_le_bytes());
+        format.extend_from_slice(&4_u16.to_le_bytes());
+        format.extend_from_slice(&16_u16.to_le_bytes());
+
+        let mut data = Vec::with_capacity(self.data.len() / channels * 4);
+        for frame in self.data.chunks_exact(channels * 2) {
+            for (side, gain) in gains.into_iter().enumerate() {
+                // INFO : A mono sample goes to both side
+                let at = side % channels * 2;
+                let sample = i32::from(i16::from_le_bytes([frame[at], frame[at + 1]]));
+                let panned = i16::try_from(sample * gain.clamp(0, 4) / 4).unwrap_or_default();
+                data.extend_from_slice(&panned.to_le_bytes());
+            }
+        }
+
+        Some(encode(&format, &data))
+    }
+
+    /// A new wav file holding the samples between `start` and `end` seconds.
+    #[cfg(feature = "audio")]
+    pub fn section(&self, start: f64, end: f64) -> Vec<u8> {
+        let data = &self.data[self.offset(start)..self.offset(end).max(self.offset(start))];
+        encode(self.format, data)
+    }
+}
+
+#[cfg(feature = "audio")]
+fn encode(format: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(data.len() + format.len() + 20);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&len_u32(4 + 8 + format.len() + 8 + data.len()));
+    bytes.extend_from_slice(b"WAVE");
+    bytes.extend_from_slice(b"fmt ");
+    bytes.extend_from_slice(&len_u32(format.len()));
+    bytes.extend_from_slice(format);
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&len_u32(data.len()));
+    bytes.extend_from_slice(data);
+    bytes
+}
+
+#[cfg(feature = "audio")]
+fn u16_at(bytes: &[u8], at: usize) -> Option<u16> {
+    bytes
+        .get(at..at + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> Option<u32> {
+    bytes
+        .get(at..at + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+#[cfg(feature = "audio")]
+fn len_u32(len: usize) -> [u8; 4] {
+    u32::try_from(len).unwrap_or(u32::MAX).to_le_bytes()
+}
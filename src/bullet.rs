@@ -0,0 +1,271 @@
+use hecs::{Entity, World};
+use macroquad::prelude::*;
+use num_complex::Complex;
+
+use crate::collision::{Collider, CollisionKind, CollisionLayer, SpatialGrid};
+use crate::components::{BlendMode, BulletSetup, Hitbox, Sprite, Transform2D};
+use crate::math::ToVec2;
+
+const ALIVE: u8 = 1 << 0;
+const BEEN_ON_SCREEN: u8 = 1 << 1;
+const GRAZED: u8 = 1 << 2;
+
+/// Index of a bullet slot inside [`BulletPool`], only valid until the bullet is killed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BulletId(pub usize);
+
+#[derive(Debug, Clone, Copy)]
+pub struct BulletCollision {
+    pub bullet: BulletId,
+    pub target: Entity,
+    pub kind: CollisionKind,
+}
+
+/// Enemy bullets live here instead of the [`World`], every field is stored in
+/// its own array and dead slots are reused through a free list so spawning and
+/// killing thousands of bullet per second don't touch hecs archetypes.
+///
+/// Bullets behave like an entity with `Enemy`, `Bullet`, `DieOffScreen` and a linear [`crate::components::MoveParams`].
+#[derive(Default)]
+pub struct BulletPool {
+    position: Vec<Complex<f32>>,
+    velocity: Vec<Complex<f32>>,
+    rotation: Vec<f32>,
+    scale: Vec<Vec2>,
+    sprite: Vec<Sprite>,
+    hitbox: Vec<Hitbox>,
+    tint: Vec<Color>,
+    blend: Vec<BlendMode>,
+    flags: Vec<u8>,
+    free: Vec<usize>,
+    alive: usize,
+}
+
+impl BulletPool {
+    pub const LAYER: CollisionLayer = CollisionLayer::ENEMY_BULLET;
+    pub const MASK: CollisionLayer = CollisionLayer::PLAYER;
+    pub const DAMAGE: f32 = 1.;
+
+    pub fn spawn(
+        &mut self,
+        transform: Transform2D,
+        velocity: Complex<f32>,
+        setup: &BulletSetup,
+    ) -> BulletId {
+        self.alive += 1;
+
+        if let Some(index) = self.free.pop() {
+            self.position[index] = transform.position;
+            self.velocity[index] = velocity;
+            self.rotation[index] = transform.rotation;
+            self.scale[index] = transform.scale;
+            self.sprite[index] = setup.sprite;
+            self.hitbox[index] = setup.hitbox;
+            self.tint[index] = setup.tint;
+            self.blend[index] = setup.blend;
+            self.flags[index] = ALIVE;
+            return BulletId(index);
+        }
+
+        self.position.push(transform.position);
+        self.velocity.push(velocity);
+        self.rotation.push(transform.rotation);
+        self.scale.push(transform.scale);
+        self.sprite.push(setup.sprite);
+        self.hitbox.push(setup.hitbox);
+        self.tint.push(setup.tint);
+        self.blend.push(setup.blend);
+        self.flags.push(ALIVE);
+        BulletId(self.flags.len() - 1)
+    }
+
+    pub fn kill(&mut self, bullet: BulletId) {
+        if self.is_alive(bullet) {
+            self.flags[bullet.0] = 0;
+            self.free.push(bullet.0);
+            self.alive -= 1;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        (0..self.flags.len()).for_each(|index| self.kill(BulletId(index)));
+    }
+
+    pub fn is_alive(&self, bullet: BulletId) -> bool {
+        self.flags
+            .get(bullet.0)
+            .is_some_and(|flags| flags & ALIVE != 0)
+    }
+
+    pub const fn len(&self) -> usize {
+        self.alive
+    }
+
+    #[cfg(debug_assertions)]
+    pub fn hitboxes(&self) -> impl Iterator<Item = (Transform2D, &Hitbox)> {
+        (0..self.flags.len())
+            .map(BulletId)
+            .filter(|bullet| self.is_alive(*bullet))
+            .map(|bullet| (self.transform(bullet), &self.hitbox[bullet.0]))
+    }
+
+    pub fn transform(&self, bullet: BulletId) -> Transform2D {
+        Transform2D::new(
+            self.position[bullet.0],
+            self.scale[bullet.0],
+            self.rotation[bullet.0],
+        )
+    }
+
+    /// Move every bullet and kill the one that left the stage after being on it.
+    pub fn update(&mut self, delta: f32) {
+        let stage = Rect::new(0., 0., 1., 1.);
+
+        for index in 0..self.flags.len() {
+            if self.flags[index] & ALIVE == 0 {
+                continue;
+            }
+
+            self.position[index] += self.velocity[index] * delta;
+
+            if stage.contains(self.position[index].to_vec2()) {
+                self.flags[index] |= BEEN_ON_SCREEN;
+            } else if self.flags[index] & BEEN_ON_SCREEN != 0 {
+                self.kill(BulletId(index));
+            }
+        }
+    }
+
+    /// Test the bullets against the entities whose [`Collider`] care about [`BulletPool::LAYER`],
+    /// the alive bullets are put in `grid` so each target only test the bullets around it.
+    pub fn collide(&self, world: &World, grid: &mut SpatialGrid) -> Vec<BulletCollision> {
+        let mut query = world.query::<(&Collider, &Transform2D, &Hitbox)>();
+        let targets = query
+            .iter()
+            .filter(|(_, (collider, _, _))| {
+                Self::MASK.intersects(collider.layer) || collider.near_mask.intersects(Self::LAYER)
+            })
+            .collect::<Vec<_>>();
+
+        let mut events = Vec::new();
+        if targets.is_empty() {
+            return events;
+        }
+
+        let slots = (0..self.flags.len())
+            .filter(|index| self.flags[*index] & ALIVE != 0)
+            .collect::<Vec<_>>();
+        let bounds = slots
+            .iter()
+            .map(|index| {
+                (
+                    self.position[*index].to_vec2(),
+                    self.hitbox[*index].shape.bounding_radius(),
+                )
+            })
+            .collect::<Vec<_>>();
+        grid.rebuild(&bounds);
+
+        let mut candidates = Vec::new();
+        for (target, (collider, target_transform, target_hitbox)) in &targets {
+            grid.query(
+                target_transform.position.to_vec2(),
+                target_hitbox.shape.bounding_radius() + Hitbox::NEAR_MARGIN,
+                &mut candidates,
+            );
+
+            for index in candidates
+                .iter()
+                .map(|candidate| slots[*candidate as usize])
+            {
+                let bullet = BulletId(index);
+                let transform = self.transform(bullet);
+                let hitbox = &self.hitbox[index];
+
+                let kind = if Self::MASK.intersects(collider.layer)
+                    && hitbox.is_intersect(&transform, target_transform, target_hitbox)
+                {
+                    CollisionKind::Hit
+                } else if self.flags[index] & GRAZED == 0
+                    && collider.near_mask.intersects(Self::LAYER)
+                    && target_hitbox.near(target_transform, &transform, hitbox)
+                {
+                    CollisionKind::Near
+                } else {
+                    continue;
+                };
+
+                events.push(BulletCollision {
+                    bullet,
+                    target: *target,
+                    kind,
+                });
+            }
+        }
+
+        events
+    }
+
+    pub fn set_grazed(&mut self, bullet: BulletId) {
+        if self.is_alive(bullet) {
+            self.flags[bullet.0] |= GRAZED;
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (BulletId, Transform2D, &Sprite, Color, BlendMode)> {
+        (0..self.flags.len())
+            .filter(|index| self.flags[*index] & ALIVE != 0)
+            .map(|index| {
+                (
+                    BulletId(index),
+                    self.transform(BulletId(index)),
+                    &self.sprite[index],
+                    self.tint[index],
+                    self.blend[index],
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cmpx, vec2};
+
+    #[test]
+    fn collide_only_report_bullets_around_the_target() {
+        let mut world = World::new();
+        let player = world.spawn((
+            Collider::new(CollisionLayer::PLAYER, CollisionLayer::ITEM)
+                .with_near(CollisionLayer::ENEMY_BULLET),
+            Transform2D::new(cmpx!(0.5), vec2!(0.01), 0.),
+            Hitbox::circle(0.01),
+        ));
+
+        let mut bullets = BulletPool::default();
+        let setup = BulletSetup::new(Sprite::new(0., 0., 8., 8.), Hitbox::circle(0.01));
+        let mut spawn = |position| {
+            bullets.spawn(
+                Transform2D::new(position, vec2!(0.01), 0.),
+                cmpx!(0.),
+                &setup,
+            )
+        };
+        let hit = spawn(cmpx!(0.5));
+        let near = spawn(cmpx!(0.53, 0.5));
+        spawn(cmpx!(0.1, 0.9));
+
+        let events = bullets.collide(&world, &mut SpatialGrid::new(8));
+        let events = events
+            .iter()
+            .map(|event| (event.bullet, event.target, event.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            vec![
+                (hit, player, CollisionKind::Hit),
+                (near, player, CollisionKind::Near)
+            ]
+        );
+    }
+}
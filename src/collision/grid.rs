@@ -0,0 +1,141 @@
+use macroquad::math::Vec2;
+
+/// Uniform grid over the 0..1 stage space, anything outside the stage is
+/// clamped into the border cells so it still can be found.
+///
+/// The grid only store index into the slice given to [`SpatialGrid::rebuild`],
+/// the entries are sorted by cell so each cell is a contiguous range.
+pub struct SpatialGrid {
+    cells_per_side: usize,
+    cell_start: Vec<u32>,
+    entries: Vec<u32>,
+}
+
+impl Default for SpatialGrid {
+    fn default() -> Self {
+        Self::new(16)
+    }
+}
+
+impl SpatialGrid {
+    pub fn new(cells_per_side: usize) -> Self {
+        let cells_per_side = cells_per_side.max(1);
+        Self {
+            cells_per_side,
+            cell_start: vec![0; cells_per_side * cells_per_side + 1],
+            entries: Vec::new(),
+        }
+    }
+
+    /// Rebuild the grid from `(position, bounding radius)` of every entry.
+    pub fn rebuild(&mut self, bounds: &[(Vec2, f32)]) {
+        self.cell_start.iter_mut().for_each(|start| *start = 0);
+
+        for (position, radius) in bounds {
+            for cell in self.cells(*position, *radius) {
+                self.cell_start[cell + 1] += 1;
+            }
+        }
+
+        for cell in 1..self.cell_start.len() {
+            self.cell_start[cell] += self.cell_start[cell - 1];
+        }
+
+        let total = *self.cell_start.last().unwrap_or(&0) as usize;
+        self.entries.resize(total, 0);
+
+        let mut cursor = self.cell_start.clone();
+        for (index, (position, radius)) in bounds.iter().enumerate() {
+            let index = u32::try_from(index).expect("the grid hold at most u32::MAX entries");
+            for cell in self.cells(*position, *radius) {
+                self.entries[cursor[cell] as usize] = index;
+                cursor[cell] += 1;
+            }
+        }
+    }
+
+    /// Collect every entry that might overlap the circle, the result is sorted and deduplicated.
+    pub fn query(&self, position: Vec2, radius: f32, result: &mut Vec<u32>) {
+        result.clear();
+
+        for cell in self.cells(position, radius) {
+            let range = self.cell_start[cell] as usize..self.cell_start[cell + 1] as usize;
+            result.extend_from_slice(&self.entries[range]);
+        }
+
+        result.sort_unstable();
+        result.dedup();
+    }
+
+    /// Index of every cell overlapped by the bounding box of the circle.
+    fn cells(&self, position: Vec2, radius: f32) -> impl Iterator<Item = usize> {
+        let cells_per_side = self.cells_per_side;
+        let (min, max) = self.cell_range(position, radius);
+        (min.1..=max.1).flat_map(move |y| (min.0..=max.0).map(move |x| y * cells_per_side + x))
+    }
+
+    // INFO : The cell is clamped into the grid so the cast can't wrap, and a grid
+    // is never big enough for its side to lose precision as a f32
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    fn cell_range(&self, position: Vec2, radius: f32) -> ((usize, usize), (usize, usize)) {
+        let cell = |value: f32| {
+            let last = self.cells_per_side - 1;
+            ((value * self.cells_per_side as f32).max(0.) as usize).min(last)
+        };
+
+        (
+            (cell(position.x - radius), cell(position.y - radius)),
+            (cell(position.x + radius), cell(position.y + radius)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(grid: &SpatialGrid, x: f32, y: f32, radius: f32) -> Vec<u32> {
+        let mut result = Vec::new();
+        grid.query(Vec2::new(x, y), radius, &mut result);
+        result
+    }
+
+    #[test]
+    fn query_find_entries_across_cell_borders() {
+        let mut grid = SpatialGrid::new(4);
+        grid.rebuild(&[
+            (Vec2::new(0.24, 0.5), 0.02),
+            (Vec2::new(0.9, 0.9), 0.01),
+            (Vec2::new(0.3, 0.5), 0.01),
+        ]);
+
+        assert_eq!(query(&grid, 0.1, 0.5, 0.01), vec![0]);
+        assert_eq!(query(&grid, 0.26, 0.5, 0.005), vec![0, 2]);
+        assert_eq!(query(&grid, 0.5, 0.5, 0.2), vec![0, 2]);
+        assert_eq!(query(&grid, 0.5, 0.5, 1.), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn outside_the_stage_is_clamped_into_the_border_cells() {
+        let mut grid = SpatialGrid::new(4);
+        grid.rebuild(&[(Vec2::new(-0.5, 1.5), 0.01), (Vec2::new(1.5, -0.2), 0.01)]);
+
+        assert_eq!(query(&grid, 0.01, 0.99, 0.001), vec![0]);
+        assert_eq!(query(&grid, 0.99, 0.01, 0.001), vec![1]);
+        assert_eq!(query(&grid, -3., -3., 0.), Vec::<u32>::new());
+        assert_eq!(query(&grid, 4., 4., 0.), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn rebuild_forget_previous_entries() {
+        let mut grid = SpatialGrid::new(4);
+        grid.rebuild(&[(Vec2::new(0.5, 0.5), 0.01)]);
+        grid.rebuild(&[]);
+
+        assert!(query(&grid, 0.5, 0.5, 1.).is_empty());
+    }
+}
@@ -0,0 +1,66 @@
+use std::ops::BitOr;
+
+use hecs::Entity;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CollisionLayer(pub u32);
+
+impl CollisionLayer {
+    pub const NONE: Self = Self(0);
+    pub const PLAYER: Self = Self(1 << 0);
+    pub const ENEMY: Self = Self(1 << 1);
+    pub const PLAYER_BULLET: Self = Self(1 << 2);
+    pub const ENEMY_BULLET: Self = Self(1 << 3);
+    pub const ITEM: Self = Self(1 << 4);
+
+    pub const fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl BitOr for CollisionLayer {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Which layer the entity belong to and which layers it want to know about,
+/// the entity need [`crate::components::Hitbox`] and [`crate::components::Transform2D`] too.
+#[derive(Debug, Clone, Copy)]
+pub struct Collider {
+    pub layer: CollisionLayer,
+    /// Layers that produce [`CollisionKind::Hit`]
+    pub mask: CollisionLayer,
+    /// Layers that produce [`CollisionKind::Near`], used for grazing
+    pub near_mask: CollisionLayer,
+}
+
+impl Collider {
+    pub const fn new(layer: CollisionLayer, mask: CollisionLayer) -> Self {
+        Self {
+            layer,
+            mask,
+            near_mask: CollisionLayer::NONE,
+        }
+    }
+
+    pub const fn with_near(self, near_mask: CollisionLayer) -> Self {
+        Self { near_mask, ..self }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollisionKind {
+    Hit,
+    Near,
+}
+
+/// `entity` is the one whose mask matched `other` layer.
+#[derive(Debug, Clone, Copy)]
+pub struct CollisionEvent {
+    pub entity: Entity,
+    pub other: Entity,
+    pub kind: CollisionKind,
+}
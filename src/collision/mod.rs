@@ -0,0 +1,7 @@
+mod grid;
+mod layer;
+mod shape;
+
+pub use grid::SpatialGrid;
+pub use layer::{Collider, CollisionEvent, CollisionKind, CollisionLayer};
+pub use shape::HitboxShape;
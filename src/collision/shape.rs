@@ -0,0 +1,329 @@
+use macroquad::math::Vec2;
+
+use crate::{components::Transform2D, math::ToVec2};
+
+const GJK_MAX_ITERATION: usize = 32;
+
+/// Shape of a [`crate::components::Hitbox`] in stage space.
+///
+/// Every shape except [`HitboxShape::Circle`] and [`HitboxShape::Rect`] follows
+/// [`Transform2D::rotation`], their long axis is the sprite vertical axis so it line up
+/// with bullet that are rotated via `dir.rot()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HitboxShape {
+    Circle {
+        radius: f32,
+    },
+    /// Axis-aligned rectangle, ignore rotation
+    Rect {
+        half_extents: Vec2,
+    },
+    RotatedRect {
+        half_extents: Vec2,
+    },
+    Ellipse {
+        radii: Vec2,
+    },
+    /// Segment along the local vertical axis with rounded ends
+    Capsule {
+        radius: f32,
+        half_length: f32,
+    },
+}
+
+impl HitboxShape {
+    /// Radius of the smallest circle around the transform position that contain the shape.
+    pub fn bounding_radius(&self) -> f32 {
+        match *self {
+            Self::Circle { radius } => radius,
+            Self::Rect { half_extents } | Self::RotatedRect { half_extents } => {
+                half_extents.length()
+            }
+            Self::Ellipse { radii } => radii.max_element(),
+            Self::Capsule {
+                radius,
+                half_length,
+            } => radius + half_length,
+        }
+    }
+
+    /// Furthest point of the shape along `dir` in stage space.
+    pub fn support(&self, transform: &Transform2D, dir: Vec2) -> Vec2 {
+        let position = transform.position.to_vec2();
+        let rotation = match self {
+            Self::Circle { .. } | Self::Rect { .. } => 0.,
+            _ => -transform.rotation,
+        };
+        let local_dir = Vec2::from_angle(-rotation).rotate(dir);
+
+        position + Vec2::from_angle(rotation).rotate(self.local_support(local_dir))
+    }
+
+    fn local_support(&self, dir: Vec2) -> Vec2 {
+        match *self {
+            Self::Circle { radius } => dir.normalize_or_zero() * radius,
+            Self::Rect { half_extents } | Self::RotatedRect { half_extents } => {
+                Vec2::new(dir.x.signum(), dir.y.signum()) * half_extents
+            }
+            Self::Ellipse { radii } => {
+                let weighted = dir * radii * radii;
+                let length = (dir * radii).length();
+                if length <= f32::EPSILON {
+                    return Vec2::ZERO;
+                }
+                weighted / length
+            }
+            Self::Capsule {
+                radius,
+                half_length,
+            } => Vec2::new(0., dir.y.signum() * half_length) + dir.normalize_or_zero() * radius,
+        }
+    }
+
+    /// Outline of the shape in stage space, mostly useful for debug drawing.
+    #[cfg(debug_assertions)]
+    pub fn outline(&self, transform: &Transform2D, segments: u16) -> Vec<Vec2> {
+        (0..segments)
+            .map(|i| {
+                let angle = f32::from(i) / f32::from(segments) * std::f32::consts::TAU;
+                self.support(transform, Vec2::from_angle(angle))
+            })
+            .collect()
+    }
+
+    /// Test whether two shape overlap, `margin` inflate `self` by that distance
+    /// in every direction which is used for grazing.
+    pub fn intersects(
+        &self,
+        transform: &Transform2D,
+        other: &Self,
+        other_transform: &Transform2D,
+        margin: f32,
+    ) -> bool {
+        if let (Self::Circle { radius: a }, Self::Circle { radius: b }) = (self, other) {
+            let offset = other_transform.position.to_vec2() - transform.position.to_vec2();
+            return offset.length_squared() <= (a + b + margin).powi(2);
+        }
+
+        self.intersects_swept(transform, transform, other, other_transform, margin)
+    }
+
+    /// Same as [`HitboxShape::intersects`] but `self` is swept from `previous` to `transform`,
+    /// so a fast entity can't skip past a thin target between two frame.
+    pub fn intersects_swept(
+        &self,
+        previous: &Transform2D,
+        transform: &Transform2D,
+        other: &Self,
+        other_transform: &Transform2D,
+        margin: f32,
+    ) -> bool {
+        let start = previous.position.to_vec2();
+        let end = transform.position.to_vec2();
+        let offset = other_transform.position.to_vec2() - (start + end) / 2.;
+        let reach =
+            self.bounding_radius() + start.distance(end) / 2. + other.bounding_radius() + margin;
+        if offset.length_squared() > reach * reach {
+            return false;
+        }
+
+        gjk(|dir| {
+            let from = self.support(previous, dir);
+            let to = self.support(transform, dir);
+            let furthest = if from.dot(dir) > to.dot(dir) {
+                from
+            } else {
+                to
+            };
+
+            furthest + dir.normalize_or_zero() * margin - other.support(other_transform, -dir)
+        })
+    }
+}
+
+/// Boolean GJK, `support` is the support function of the Minkowski difference
+/// between the two shape, it return true if it contain the origin.
+fn gjk(support: impl Fn(Vec2) -> Vec2) -> bool {
+    let mut simplex = [Vec2::ZERO; 3];
+    simplex[0] = support(Vec2::X);
+    let mut len = 1;
+    let mut dir = -simplex[0];
+
+    for _ in 0..GJK_MAX_ITERATION {
+        if dir.length_squared() <= f32::EPSILON * f32::EPSILON {
+            return true;
+        }
+
+        let point = support(dir);
+        if point.dot(dir) < 0. {
+            return false;
+        }
+
+        simplex[len] = point;
+        len += 1;
+
+        let a = simplex[len - 1];
+        let ao = -a;
+
+        if len == 2 {
+            let ab = simplex[0] - a;
+            if ab.dot(ao) > 0. {
+                dir = towards(ab.perp(), ao);
+            } else {
+                simplex[0] = a;
+                len = 1;
+                dir = ao;
+            }
+            continue;
+        }
+
+        let (b, c) = (simplex[1], simplex[0]);
+        let ab = b - a;
+        let ac = c - a;
+        let ab_normal = towards(ab.perp(), -ac);
+        let ac_outward = towards(ac.perp(), -ab);
+
+        if ab_normal.dot(ao) > 0. {
+            simplex = [b, a, Vec2::ZERO];
+            len = 2;
+            dir = ab_normal;
+        } else if ac_outward.dot(ao) > 0. {
+            simplex = [c, a, Vec2::ZERO];
+            len = 2;
+            dir = ac_outward;
+        } else {
+            return true;
+        }
+    }
+
+    // INFO : Not converging mean the shapes barely touch, it doesn't count as a hit
+    false
+}
+
+/// Flip `vector` so it point to the same half plane as `target`.
+fn towards(vector: Vec2, target: Vec2) -> Vec2 {
+    if vector.dot(target) < 0. {
+        -vector
+    } else {
+        vector
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+    use crate::{cmpx, components::Hitbox, vec2};
+
+    const BULLET: f32 = 0.005;
+
+    fn at(x: f32, y: f32) -> Transform2D {
+        rotated(x, y, 0.)
+    }
+
+    fn rotated(x: f32, y: f32, rotation: f32) -> Transform2D {
+        Transform2D::new(cmpx!(x, y), vec2!(0.1), rotation)
+    }
+
+    /// Whether a circle bullet at `position` touch `hitbox` placed at `transform`.
+    fn bullet_hits(hitbox: Hitbox, transform: &Transform2D, position: Transform2D) -> bool {
+        Hitbox::circle(BULLET)
+            .shape
+            .intersects(&position, &hitbox.shape, transform, 0.)
+    }
+
+    #[test]
+    fn circles() {
+        let target = at(0.5, 0.5);
+        assert!(bullet_hits(Hitbox::circle(0.01), &target, at(0.514, 0.5)));
+        assert!(!bullet_hits(Hitbox::circle(0.01), &target, at(0.516, 0.5)));
+    }
+
+    #[test]
+    fn circle_and_rect() {
+        let rect = Hitbox::rect(0.1, 0.02);
+        let target = at(0.5, 0.5);
+
+        assert!(bullet_hits(rect, &target, at(0.554, 0.5)));
+        assert!(!bullet_hits(rect, &target, at(0.556, 0.5)));
+        assert!(bullet_hits(rect, &target, at(0.5, 0.514)));
+        assert!(!bullet_hits(rect, &target, at(0.5, 0.516)));
+        // INFO : Inside the bounding box of the inflated rect but past the rounded corner
+        assert!(!bullet_hits(rect, &target, at(0.554, 0.514)));
+    }
+
+    #[test]
+    fn rect_ignore_rotation() {
+        let rect = Hitbox::rect(0.01, 0.1);
+        let target = rotated(0.5, 0.5, FRAC_PI_2);
+
+        assert!(bullet_hits(rect, &target, at(0.5, 0.545)));
+        assert!(!bullet_hits(rect, &target, at(0.545, 0.5)));
+    }
+
+    #[test]
+    fn rotated_rect_follow_rotation() {
+        let rect = Hitbox::rotated_rect(0.01, 0.1);
+
+        let upright = at(0.5, 0.5);
+        assert!(bullet_hits(rect, &upright, at(0.5, 0.545)));
+        assert!(!bullet_hits(rect, &upright, at(0.545, 0.5)));
+
+        let lying = rotated(0.5, 0.5, FRAC_PI_2);
+        assert!(bullet_hits(rect, &lying, at(0.545, 0.5)));
+        assert!(!bullet_hits(rect, &lying, at(0.5, 0.545)));
+    }
+
+    #[test]
+    fn capsule_rounded_ends() {
+        let capsule = Hitbox::capsule(0.01, 0.1);
+        let target = at(0.5, 0.5);
+
+        assert!(bullet_hits(capsule, &target, at(0.5, 0.554)));
+        assert!(!bullet_hits(capsule, &target, at(0.5, 0.556)));
+        assert!(bullet_hits(capsule, &target, at(0.514, 0.5)));
+        assert!(!bullet_hits(capsule, &target, at(0.516, 0.5)));
+        // INFO : A rect of the same size would catch this one
+        assert!(bullet_hits(capsule, &target, at(0.509, 0.549)));
+        assert!(!bullet_hits(capsule, &target, at(0.511, 0.551)));
+    }
+
+    #[test]
+    fn ellipse_edges() {
+        let ellipse = Hitbox::ellipse(0.1, 0.02);
+        let target = at(0.5, 0.5);
+
+        assert!(bullet_hits(ellipse, &target, at(0.554, 0.5)));
+        assert!(!bullet_hits(ellipse, &target, at(0.556, 0.5)));
+        assert!(bullet_hits(ellipse, &target, at(0.5, 0.514)));
+        assert!(!bullet_hits(ellipse, &target, at(0.5, 0.516)));
+        // INFO : The edge is at 0.506 there, a rect would catch it
+        assert!(!bullet_hits(ellipse, &target, at(0.54, 0.513)));
+
+        let lying = rotated(0.5, 0.5, FRAC_PI_2);
+        assert!(bullet_hits(ellipse, &lying, at(0.5, 0.554)));
+        assert!(!bullet_hits(ellipse, &lying, at(0.554, 0.5)));
+    }
+
+    #[test]
+    fn margin_inflate_the_shape() {
+        let rect = Hitbox::rotated_rect(0.02, 0.02).shape;
+        let target = at(0.5, 0.5);
+        let bullet = Hitbox::circle(BULLET).shape;
+
+        assert!(!bullet.intersects(&at(0.52, 0.5), &rect, &target, 0.));
+        assert!(bullet.intersects(&at(0.52, 0.5), &rect, &target, 0.01));
+    }
+
+    #[test]
+    fn sweep_catch_a_skipped_target() {
+        let wall = Hitbox::rect(0.1, 0.004).shape;
+        let target = at(0.5, 0.5);
+        let bullet = Hitbox::circle(BULLET).shape;
+
+        assert!(!bullet.intersects(&at(0.5, 0.45), &wall, &target, 0.));
+        assert!(bullet.intersects_swept(&at(0.5, 0.55), &at(0.5, 0.45), &wall, &target, 0.));
+        assert!(!bullet.intersects_swept(&at(0.6, 0.55), &at(0.6, 0.45), &wall, &target, 0.));
+    }
+}
@@ -5,6 +5,7 @@ use num_complex::Complex;
 
 use crate::{
     cmpx,
+    collision::HitboxShape,
     math::{ComplexExt, ToComplex, ToVec2 as _},
     time::Timer,
     utils::rand_vec2,
@@ -17,6 +18,38 @@ pub struct Enemy;
 pub struct Boss;
 pub struct Bullet;
 pub struct DieOffScreen;
+pub struct Grazed;
+/// Skipped by movement, attack and animation systems, set from the debug inspector.
+pub struct Frozen;
+/// Enemy bullets pass through it, set with the `god` console command.
+pub struct Invincible;
+
+/// What an entity is, so debug tools can tell them apart.
+#[cfg(debug_assertions)]
+#[derive(Debug, Clone, Copy)]
+pub struct Name(pub &'static str);
+
+/// Entity that move far enough in one frame to skip past a thin hitbox,
+/// collision is tested along the path from the previous position instead.
+#[derive(Debug, Clone, Copy)]
+pub struct FastMoving {
+    pub previous_position: Complex<f32>,
+}
+
+impl FastMoving {
+    pub const fn new(position: Complex<f32>) -> Self {
+        Self {
+            previous_position: position,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Damage(pub f32);
+#[derive(Debug, Clone, Copy)]
+pub enum Item {
+    Point(u64),
+}
 #[derive(Debug, Clone)]
 pub struct BeenOnScreen(pub bool);
 #[derive(Debug, Clone)]
@@ -36,6 +69,7 @@ impl Wanderable {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Text {
     Left(String),
     Center(String),
@@ -60,31 +94,180 @@ impl Cooldown {
 
 pub struct HealthBar(pub Rect);
 
-// It uses [`Resources`] sprite
-#[derive(Debug)]
-pub struct ParallaxBackground(pub Sprite);
+#[derive(Debug, Clone, Copy)]
+pub struct Tint(pub Color);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum BlendMode {
+    #[default]
+    Normal,
+    Additive,
+}
+
+/// Animate the alpha of [`Tint`], the entity need a [`Tint`] for it to do anything.
+#[derive(Debug, Clone)]
+pub struct Fade {
+    timer: Timer,
+    from: f32,
+    to: f32,
+}
+
+impl Fade {
+    pub fn new(duration: f32, from: f32, to: f32) -> Self {
+        Self {
+            timer: Timer::new(duration, false),
+            from,
+            to,
+        }
+    }
+
+    pub fn fade_in(duration: f32) -> Self {
+        Self::new(duration, 0., 1.)
+    }
+
+    pub fn fade_out(duration: f32) -> Self {
+        Self::new(duration, 1., 0.)
+    }
+
+    pub fn update(&mut self) -> f32 {
+        self.timer.update();
+        (self.from - self.to).mul_add(self.timer.progress(), self.to)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.timer.progress() <= 0.
+    }
+}
+
+/// Ask the music player to change track, despawned once handled.
+#[derive(Debug, Clone)]
+pub enum MusicRequest {
+    Play { track: String, fade: f32 },
+    Stop { fade: f32 },
+}
+
+/// Light the sprite up with `color` for a moment after it took damage.
+#[derive(Debug, Clone)]
+pub struct HitFlash {
+    pub color: Color,
+    timer: Timer,
+}
+
+impl HitFlash {
+    pub fn new(color: Color, duration: f32) -> Self {
+        Self {
+            color,
+            timer: Timer::new(duration, false),
+        }
+    }
+
+    pub fn update(&mut self) {
+        self.timer.update();
+    }
+
+    /// Goes from 1 when hit down to 0 when the flash is over.
+    pub fn strength(&self) -> f32 {
+        self.timer.progress()
+    }
+}
+
+/// Draw order on the stage, later variant are drawn on top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Layer {
+    Background,
+    Enemy,
+    Item,
+    Player,
+    PlayerBullet,
+    EnemyBullet,
+    #[default]
+    Effect,
+    PlayerHitbox,
+    Hud,
+    #[cfg(debug_assertions)]
+    Debug,
+}
+
+/// One tiled layer of a [`ParallaxBackground`], sizes are in stage unit.
+#[derive(Debug, Clone, Copy)]
+pub struct ParallaxLayer {
+    pub sprite: Sprite,
+    pub tile: Vec2,
+    pub velocity: Vec2,
+    pub tint: Color,
+    pub offset: Vec2,
+}
+
+impl ParallaxLayer {
+    pub const fn new(sprite: Sprite, tile: Vec2, velocity: Vec2) -> Self {
+        Self {
+            sprite,
+            tile,
+            velocity,
+            tint: WHITE,
+            offset: Vec2::ZERO,
+        }
+    }
+
+    pub const fn with_tint(mut self, tint: Color) -> Self {
+        self.tint = tint;
+        self
+    }
+}
+
+/// Tiled layers covering the stage, drawn back to front. Put a [`Tint`] and
+/// [`Fade`] on the entity to fade it in or out.
+#[derive(Debug, Clone)]
+pub struct ParallaxBackground {
+    pub layers: Vec<ParallaxLayer>,
+    pub speed: f32,
+    speed_change: Option<(f32, f32, Timer)>,
+}
+
+impl ParallaxBackground {
+    pub const fn new(layers: Vec<ParallaxLayer>) -> Self {
+        Self {
+            layers,
+            speed: 1.,
+            speed_change: None,
+        }
+    }
+
+    /// Ease every layer scroll speed to `speed` times its velocity.
+    pub fn set_speed(&mut self, speed: f32, duration: f32) {
+        if duration <= 0. {
+            self.speed = speed;
+            self.speed_change = None;
+            return;
+        }
+
+        self.speed_change = Some((self.speed, speed, Timer::new(duration, false)));
+    }
+
+    pub fn update(&mut self, delta: f32) {
+        if let Some((from, to, timer)) = &mut self.speed_change {
+            timer.update();
+            self.speed = (*from - *to).mul_add(timer.progress(), *to);
+            if timer.progress() <= 0. {
+                self.speed_change = None;
+            }
+        }
+
+        for layer in &mut self.layers {
+            layer.offset =
+                (layer.offset + layer.velocity * self.speed * delta).rem_euclid(layer.tile);
+        }
+    }
+}
 
 // It uses [`Resources`] sprite
 #[derive(Debug, Clone, Copy)]
 pub struct Sprite(pub Rect);
 
 impl Sprite {
-    // INFO : You can't change this, it's depend on the sprite atlas
-    const WIDTH: u32 = 16;
-    const HEIGHT: u32 = 16;
-
     pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
         Self(Rect::new(x, y, width, height))
     }
-
-    pub fn new_from_index(x: u32, y: u32) -> Self {
-        Self(Rect::new(
-            (x * Self::WIDTH) as f32,
-            (y * Self::HEIGHT) as f32,
-            Self::WIDTH as f32,
-            Self::HEIGHT as f32,
-        ))
-    }
 }
 pub struct Movement {
     timer: Timer,
@@ -98,6 +281,16 @@ impl Movement {
             movement,
         }
     }
+
+    #[cfg(debug_assertions)]
+    pub const fn name(&self) -> &'static str {
+        self.movement.name()
+    }
+
+    #[cfg(debug_assertions)]
+    pub const fn remaining(&self) -> f32 {
+        self.timer.remaining()
+    }
 }
 
 pub struct Moves(pub VecDeque<Movement>);
@@ -208,7 +401,31 @@ impl Moves {
 }
 
 #[derive(Debug, Clone)]
-pub struct BulletSetup(pub Sprite);
+pub struct BulletSetup {
+    pub sprite: Sprite,
+    pub hitbox: Hitbox,
+    pub tint: Color,
+    pub blend: BlendMode,
+}
+
+impl BulletSetup {
+    pub const fn new(sprite: Sprite, hitbox: Hitbox) -> Self {
+        Self {
+            sprite,
+            hitbox,
+            tint: WHITE,
+            blend: BlendMode::Normal,
+        }
+    }
+
+    pub const fn with_tint(self, tint: Color) -> Self {
+        Self { tint, ..self }
+    }
+
+    pub const fn with_blend(self, blend: BlendMode) -> Self {
+        Self { blend, ..self }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct BossMove {
@@ -270,6 +487,25 @@ pub enum Move {
     MoveWanderLinear(Rect, f32, f32),
 }
 
+#[cfg(debug_assertions)]
+impl Move {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::MoveNext(..) => "next",
+            Self::MoveFromToward(..) => "from_toward",
+            Self::MoveTowardExp(..) => "toward_exp",
+            Self::MoveFromTowardExp(..) => "from_toward_exp",
+            Self::MoveTowards(..) => "towards",
+            Self::MoveLinear(..) => "linear",
+            Self::MoveAccelerated(..) => "accelerated",
+            Self::MoveAccelerated2(..) => "accelerated_2",
+            Self::MoveDampen(..) => "dampen",
+            Self::MoveDampenRetention(..) => "dampen_retention",
+            Self::MoveWanderLinear(..) => "wander_linear",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Default)]
 pub struct MoveParams {
     pub velocity: Complex<f32>,
@@ -445,24 +681,54 @@ impl Transform2D {
     pub fn position(&self) -> &Complex<f32> {
         &self.position
     }
-
-    pub fn scale(&self) -> &Vec2 {
-        &self.scale
-    }
-
-    pub fn rotation(&self) -> &f32 {
-        &self.rotation
-    }
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct Hitbox {
-    pub radius: f32,
+    pub shape: HitboxShape,
 }
 
 impl Hitbox {
-    pub fn new(radius: f32) -> Self {
-        Self { radius }
+    pub const NEAR_MARGIN: f32 = 0.05;
+
+    pub const fn circle(radius: f32) -> Self {
+        Self {
+            shape: HitboxShape::Circle { radius },
+        }
+    }
+
+    pub fn rect(width: f32, height: f32) -> Self {
+        Self {
+            shape: HitboxShape::Rect {
+                half_extents: vec2!(width, height) / 2.,
+            },
+        }
+    }
+
+    pub fn rotated_rect(width: f32, height: f32) -> Self {
+        Self {
+            shape: HitboxShape::RotatedRect {
+                half_extents: vec2!(width, height) / 2.,
+            },
+        }
+    }
+
+    pub fn ellipse(width: f32, height: f32) -> Self {
+        Self {
+            shape: HitboxShape::Ellipse {
+                radii: vec2!(width, height) / 2.,
+            },
+        }
+    }
+
+    // INFO : `length` is the total length including the rounded ends
+    pub fn capsule(radius: f32, length: f32) -> Self {
+        Self {
+            shape: HitboxShape::Capsule {
+                radius,
+                half_length: (length / 2. - radius).max(0.),
+            },
+        }
     }
 
     pub fn is_intersect(
@@ -471,23 +737,38 @@ impl Hitbox {
         target_pos: &Transform2D,
         target_hitbox: &Self,
     ) -> bool {
-        let distance_squared = vec2(current_pos.position().re, current_pos.position().im)
-            .distance_squared(vec2(target_pos.position().re, target_pos.position().im));
-        let sum_of_radii_squared = (self.radius + target_hitbox.radius).powi(2);
-        distance_squared <= sum_of_radii_squared
+        self.shape
+            .intersects(current_pos, &target_hitbox.shape, target_pos, 0.)
     }
+
+    pub fn is_intersect_swept(
+        &self,
+        previous_pos: &Transform2D,
+        current_pos: &Transform2D,
+        target_pos: &Transform2D,
+        target_hitbox: &Self,
+    ) -> bool {
+        self.shape.intersects_swept(
+            previous_pos,
+            current_pos,
+            &target_hitbox.shape,
+            target_pos,
+            0.,
+        )
+    }
+
     pub fn near(
         &self,
         current_pos: &Transform2D,
         target_pos: &Transform2D,
         target_hitbox: &Self,
     ) -> bool {
-        let distance_squared = current_pos
-            .position()
-            .to_vec2()
-            .distance_squared(target_pos.position().to_vec2());
-        let sum_of_radii_squared = (self.radius + 0.05 + target_hitbox.radius).powi(2);
-        distance_squared <= sum_of_radii_squared
+        self.shape.intersects(
+            current_pos,
+            &target_hitbox.shape,
+            target_pos,
+            Self::NEAR_MARGIN,
+        )
     }
 }
 
@@ -523,6 +804,11 @@ impl Hitpoint {
         return self.hp < 0.;
     }
 
+    /// Below a quarter of its hitpoint, never for invulnerable phases.
+    pub fn is_low(&self) -> bool {
+        !self.invulnerable && self.hp < self.max_hp * 0.25
+    }
+
     pub fn damage(&mut self, damage: f32) -> bool {
         if !self.invulnerable {
             self.hp -= damage;
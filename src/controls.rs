@@ -2,25 +2,109 @@ use std::collections::HashMap;
 
 use macroquad::{miniquad::KeyMods, prelude::*};
 
-pub fn init_controls() -> Controls {
+pub fn init_controls(bindings: &[(Action, KeyCode)]) -> Controls {
     let mut controls = Controls::new();
 
-    controls.add(Action::Attack, Combination::Single(KeyCode::Z));
-    controls.add(Action::Focus, Combination::Single(KeyCode::LeftShift));
-    controls.add(Action::Spell, Combination::Single(KeyCode::X));
+    for (action, key) in bindings {
+        controls.add(*action, Combination::Single(*key));
+    }
 
-    controls.add(Action::Up, Combination::Single(KeyCode::Up));
-    controls.add(Action::Left, Combination::Single(KeyCode::Left));
-    controls.add(Action::Right, Combination::Single(KeyCode::Right));
-    controls.add(Action::Down, Combination::Single(KeyCode::Down));
+    controls
+}
 
-    controls.add(Action::Escape, Combination::Single(KeyCode::Escape));
-    controls.add(Action::Accept, Combination::Single(KeyCode::Enter));
+pub const DEFAULT_BINDINGS: [(Action, KeyCode); 9] = [
+    (Action::Attack, KeyCode::Z),
+    (Action::Focus, KeyCode::LeftShift),
+    (Action::Spell, KeyCode::X),
+    (Action::Up, KeyCode::Up),
+    (Action::Left, KeyCode::Left),
+    (Action::Right, KeyCode::Right),
+    (Action::Down, KeyCode::Down),
+    (Action::Escape, KeyCode::Escape),
+    (Action::Accept, KeyCode::Enter),
+];
+
+/// Keys that can be bound in the settings, written with their [`KeyCode`] name.
+const BINDABLE_KEYS: [KeyCode; 62] = [
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+    KeyCode::Key0,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Space,
+    KeyCode::Enter,
+    KeyCode::Escape,
+    KeyCode::Tab,
+    KeyCode::Backspace,
+    KeyCode::LeftShift,
+    KeyCode::RightShift,
+    KeyCode::LeftControl,
+    KeyCode::RightControl,
+    KeyCode::LeftAlt,
+    KeyCode::RightAlt,
+    KeyCode::Comma,
+    KeyCode::Period,
+    KeyCode::Slash,
+    KeyCode::Semicolon,
+    KeyCode::Apostrophe,
+    KeyCode::LeftBracket,
+    KeyCode::RightBracket,
+    KeyCode::Minus,
+    KeyCode::Equal,
+    KeyCode::Backslash,
+    KeyCode::GraveAccent,
+];
+
+pub fn key_name(key: KeyCode) -> String {
+    format!("{key:?}")
+}
 
-    controls
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    BINDABLE_KEYS
+        .into_iter()
+        .find(|key| key_name(*key).eq_ignore_ascii_case(name))
 }
 
-#[derive(Hash, Eq, PartialEq)]
+pub fn is_bindable(key: KeyCode) -> bool {
+    BINDABLE_KEYS.contains(&key)
+}
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
 pub enum Action {
     Attack,
     Spell,
@@ -35,6 +119,22 @@ pub enum Action {
     Accept,
 }
 
+impl Action {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Attack => "attack",
+            Self::Spell => "spell",
+            Self::Focus => "focus",
+            Self::Up => "up",
+            Self::Right => "right",
+            Self::Left => "left",
+            Self::Down => "down",
+            Self::Escape => "escape",
+            Self::Accept => "accept",
+        }
+    }
+}
+
 pub enum Combination {
     Single(KeyCode),
     Double(KeyCode, KeyMods),
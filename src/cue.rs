@@ -0,0 +1,31 @@
+/// Something that happened during gameplay that the audio should react to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cue {
+    PlayerShot,
+    EnemyHit,
+    EnemyDeath,
+    Graze,
+    Pickup,
+    Bomb,
+    PlayerDeath,
+    BossLowHp,
+}
+
+/// Cues of the current frame, with the x position of where they happened
+/// in stage unit so they can be panned.
+#[derive(Debug, Default)]
+pub struct Cues(Vec<(Cue, f32)>);
+
+impl Cues {
+    pub fn push(&mut self, cue: Cue, x: f32) {
+        self.0.push((cue, x));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Cue, f32)> + '_ {
+        self.0.iter().copied()
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
@@ -0,0 +1,324 @@
+use std::{collections::VecDeque, str::FromStr};
+
+use macroquad::prelude::*;
+
+use crate::{
+    cmpx,
+    components::{Layer, Text, Transform2D},
+    renderer::DrawList,
+    utils::float_iter,
+    vec2, Game,
+};
+
+const TOGGLE_KEY: KeyCode = KeyCode::GraveAccent;
+const TOGGLE_CHAR: char = '`';
+const OUTPUT_LINES: usize = 10;
+const HISTORY: usize = 64;
+
+const TEXT_SIZE: f32 = 0.018;
+const LINE_SPACING: f32 = 0.022;
+// INFO : Room for the output and the input line
+const HEIGHT: f32 = 0.27;
+const BACKGROUND: Color = Color::new(0., 0., 0., 0.75);
+
+/// What a command print back, the error is shown in red.
+pub type CommandResult = Result<String, String>;
+
+pub struct Command {
+    pub name: &'static str,
+    pub usage: &'static str,
+    /// Words offered by tab completion, one list per argument.
+    pub completions: &'static [&'static [&'static str]],
+    pub run: fn(&mut Game, &[&str]) -> CommandResult,
+}
+
+/// Every command the console know, modules register the ones for the systems they own.
+#[derive(Default)]
+pub struct CommandRegistry {
+    commands: Vec<Command>,
+}
+
+impl CommandRegistry {
+    pub fn register(&mut self, command: Command) {
+        self.commands.retain(|other| other.name != command.name);
+        self.commands.push(command);
+        self.commands.sort_by_key(|command| command.name);
+    }
+
+    pub fn usages(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.commands.iter().map(|command| command.usage)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Command> {
+        self.commands.iter().find(|command| command.name == name)
+    }
+
+    /// Words that can follow `words`, the last one being the word being typed.
+    fn candidates(&self, words: &[&str]) -> Vec<&'static str> {
+        let (typed, previous) = words.split_last().unwrap_or((&"", &[]));
+        let options = match previous.split_first() {
+            None => self.commands.iter().map(|command| command.name).collect(),
+            Some((name, args)) => self
+                .get(name)
+                .and_then(|command| command.completions.get(args.len()))
+                .map_or_else(Vec::new, |options| options.to_vec()),
+        };
+
+        options
+            .into_iter()
+            .filter(|option| option.starts_with(typed))
+            .collect()
+    }
+}
+
+/// Parse the `index`th argument, `name` is used in the error message.
+pub fn arg<T: FromStr>(args: &[&str], index: usize, name: &str) -> Result<T, String> {
+    let value = args.get(index).ok_or_else(|| format!("missing {name}"))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid {name} `{value}`"))
+}
+
+/// `on` and `off`, nothing toggle `current`.
+pub fn toggle_arg(args: &[&str], current: bool) -> Result<bool, String> {
+    match args.first() {
+        None => Ok(!current),
+        Some(&"on") => Ok(true),
+        Some(&"off") => Ok(false),
+        Some(value) => Err(format!("expected `on` or `off`, got `{value}`")),
+    }
+}
+
+/// Text console toggled with the backtick key in debug builds, the game is
+/// paused while it is open.
+pub struct Console {
+    pub open: bool,
+    pub registry: CommandRegistry,
+    input: String,
+    history: Vec<String>,
+    browsing: Option<usize>,
+    output: VecDeque<(String, Color)>,
+}
+
+impl Console {
+    pub const fn new(registry: CommandRegistry) -> Self {
+        Self {
+            open: false,
+            registry,
+            input: String::new(),
+            history: Vec::new(),
+            browsing: None,
+            output: VecDeque::new(),
+        }
+    }
+
+    pub fn print(&mut self, line: impl Into<String>, color: Color) {
+        if self.output.len() == OUTPUT_LINES {
+            self.output.pop_front();
+        }
+        self.output.push_back((line.into(), color));
+    }
+
+    /// Handle the keyboard, the line to run is returned once Enter is pressed.
+    pub fn update(&mut self) -> Option<String> {
+        if is_key_pressed(TOGGLE_KEY) {
+            self.open = !self.open;
+            // INFO : Drop what was typed while it was closed
+            while get_char_pressed().is_some() {}
+            return None;
+        }
+        if !self.open {
+            return None;
+        }
+
+        while let Some(char) = get_char_pressed() {
+            if !char.is_control() && char != TOGGLE_CHAR {
+                self.input.push(char);
+            }
+        }
+
+        if is_key_pressed(KeyCode::Escape) {
+            self.open = false;
+        } else if is_key_pressed(KeyCode::Backspace) {
+            self.input.pop();
+        } else if is_key_pressed(KeyCode::Tab) {
+            self.complete();
+        } else if is_key_pressed(KeyCode::Up) {
+            self.browse(true);
+        } else if is_key_pressed(KeyCode::Down) {
+            self.browse(false);
+        } else if is_key_pressed(KeyCode::Enter) {
+            return self.submit();
+        }
+
+        None
+    }
+
+    fn submit(&mut self) -> Option<String> {
+        let line = std::mem::take(&mut self.input).trim().to_string();
+        self.browsing = None;
+        if line.is_empty() {
+            return None;
+        }
+
+        if self.history.last() != Some(&line) {
+            if self.history.len() == HISTORY {
+                self.history.remove(0);
+            }
+            self.history.push(line.clone());
+        }
+        self.print(format!("> {line}"), GRAY);
+        Some(line)
+    }
+
+    /// Walk the history, `back` toward older lines.
+    fn browse(&mut self, back: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+
+        let last = self.history.len() - 1;
+        self.browsing = match (self.browsing, back) {
+            (None, true) => Some(last),
+            (Some(index), true) => Some(index.saturating_sub(1)),
+            (Some(index), false) if index < last => Some(index + 1),
+            (_, false) => None,
+        };
+        self.input = self
+            .browsing
+            .map_or_else(String::new, |index| self.history[index].clone());
+    }
+
+    fn complete(&mut self) {
+        let mut words = self.input.split_whitespace().collect::<Vec<_>>();
+        if self.input.is_empty() || self.input.ends_with(' ') {
+            words.push("");
+        }
+
+        let candidates = self.registry.candidates(&words);
+        let Some(first) = candidates.first() else {
+            return;
+        };
+
+        // INFO : Complete up to where the candidates disagree and list them
+        let common = candidates.iter().fold(*first, |common, candidate| {
+            let same = common
+                .char_indices()
+                .zip(candidate.chars())
+                .find(|((_, a), b)| a != b)
+                .map_or_else(|| common.len().min(candidate.len()), |((at, _), _)| at);
+            &common[..same]
+        });
+
+        words.pop();
+        words.push(common);
+        self.input = words.join(" ");
+        if candidates.len() == 1 {
+            self.input.push(' ');
+        } else {
+            self.print(candidates.join("  "), WHITE);
+        }
+    }
+
+    pub fn draw(&self, commands: &mut DrawList) {
+        if !self.open {
+            return;
+        }
+
+        commands.rect(Layer::Debug, Rect::new(0., 0., 1., HEIGHT), BACKGROUND);
+
+        let input = (format!("> {}_", self.input), WHITE);
+        let lines = self.output.iter().cloned().chain(std::iter::once(input));
+        for ((line, color), y) in lines.zip(float_iter(LINE_SPACING, 1., LINE_SPACING)) {
+            commands.text(
+                Layer::Debug,
+                &Text::Left(line),
+                &Transform2D::new(cmpx!(0.02, y), vec2!(TEXT_SIZE), 0.),
+                color,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{arg, toggle_arg, Command, CommandRegistry, Console};
+
+    const GOD: &[&[&str]] = &[&["on", "off"]];
+    const SPAWN: &[&[&str]] = &[&["red_alien", "red_alien_wait", "boss_1"]];
+
+    fn registry() -> CommandRegistry {
+        let mut registry = CommandRegistry::default();
+        for (name, completions) in [("god", GOD), ("spawn", SPAWN), ("seek", &[])] {
+            registry.register(Command {
+                name,
+                usage: name,
+                completions,
+                run: |_, _| Ok(String::new()),
+            });
+        }
+        registry
+    }
+
+    fn complete(input: &str) -> String {
+        let mut console = Console::new(registry());
+        console.input = input.to_string();
+        console.complete();
+        console.input
+    }
+
+    #[test]
+    fn candidates_follow_the_typed_words() {
+        let registry = registry();
+
+        assert_eq!(registry.candidates(&[""]), vec!["god", "seek", "spawn"]);
+        assert_eq!(registry.candidates(&["s"]), vec!["seek", "spawn"]);
+        assert_eq!(registry.candidates(&["god", "o"]), vec!["on", "off"]);
+        assert!(registry.candidates(&["god", "on", ""]).is_empty());
+        assert!(registry.candidates(&["nope", ""]).is_empty());
+    }
+
+    #[test]
+    fn complete_up_to_where_candidates_disagree() {
+        assert_eq!(complete("sp"), "spawn ");
+        assert_eq!(complete("spawn red"), "spawn red_alien");
+        assert_eq!(complete("spawn b"), "spawn boss_1 ");
+        assert_eq!(complete("god of"), "god off ");
+        assert_eq!(complete("x"), "x");
+    }
+
+    #[test]
+    fn history_browse_back_and_forth() {
+        let mut console = Console::new(registry());
+        for line in ["god on", "seek 10", "seek 10", "  "] {
+            console.input = line.to_string();
+            console.submit();
+        }
+        assert_eq!(console.history, vec!["god on", "seek 10"]);
+
+        console.browse(true);
+        assert_eq!(console.input, "seek 10");
+        console.browse(true);
+        console.browse(true);
+        assert_eq!(console.input, "god on");
+        console.browse(false);
+        assert_eq!(console.input, "seek 10");
+        console.browse(false);
+        assert_eq!(console.input, "");
+    }
+
+    #[test]
+    fn arguments_are_parsed_or_explained() {
+        assert_eq!(arg::<f32>(&["0.5"], 0, "x"), Ok(0.5));
+        assert_eq!(arg::<f32>(&[], 0, "x"), Err("missing x".to_string()));
+        assert_eq!(
+            arg::<u32>(&["-1"], 0, "lives"),
+            Err("invalid lives `-1`".to_string())
+        );
+
+        assert_eq!(toggle_arg(&[], true), Ok(false));
+        assert_eq!(toggle_arg(&["on"], false), Ok(true));
+        assert_eq!(toggle_arg(&["off"], true), Ok(false));
+        assert!(toggle_arg(&["maybe"], true).is_err());
+    }
+}
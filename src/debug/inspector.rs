@@ -0,0 +1,323 @@
+use hecs::{Entity, World};
+use macroquad::prelude::*;
+use num_complex::Complex;
+
+use crate::{
+    cmpx,
+    components::{
+        AttackMove, BossMoves, Frozen, Hitpoint, Layer, MoveParams, Moves, Name, Text, Transform2D,
+    },
+    renderer::DrawList,
+    utils::float_iter,
+    vec2,
+};
+
+const TOGGLE_KEY: KeyCode = KeyCode::F4;
+const NEXT_KEY: KeyCode = KeyCode::Tab;
+const FIELD_KEYS: (KeyCode, KeyCode) = (KeyCode::LeftBracket, KeyCode::RightBracket);
+const EDIT_KEYS: (KeyCode, KeyCode) = (KeyCode::Minus, KeyCode::Equal);
+const DESPAWN_KEY: KeyCode = KeyCode::Delete;
+const FREEZE_KEY: KeyCode = KeyCode::F;
+
+// INFO : In stage unit, how far from an entity a click still select it
+const PICK_RADIUS: f32 = 0.05;
+const POSITION_STEP: f32 = 0.01;
+const VELOCITY_STEP: f32 = 0.05;
+const HITPOINT_STEP: f32 = 1.;
+
+const TEXT_SIZE: f32 = 0.018;
+const LINE_SPACING: f32 = 0.022;
+const TEXT_COLOR: Color = YELLOW;
+const MARKER_COLOR: Color = Color::new(1., 1., 0., 0.3);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    PositionX,
+    PositionY,
+    VelocityX,
+    VelocityY,
+    Hitpoint,
+    Invulnerable,
+}
+
+const FIELDS: [Field; 6] = [
+    Field::PositionX,
+    Field::PositionY,
+    Field::VelocityX,
+    Field::VelocityY,
+    Field::Hitpoint,
+    Field::Invulnerable,
+];
+
+impl Field {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::PositionX => "position x",
+            Self::PositionY => "position y",
+            Self::VelocityX => "velocity x",
+            Self::VelocityY => "velocity y",
+            Self::Hitpoint => "hp",
+            Self::Invulnerable => "invulnerable",
+        }
+    }
+
+    fn value(self, world: &World, entity: Entity) -> Option<String> {
+        let transform = || world.get::<&Transform2D>(entity).ok();
+        let movement = || world.get::<&MoveParams>(entity).ok();
+        let hitpoint = || world.get::<&Hitpoint>(entity).ok();
+
+        match self {
+            Self::PositionX => transform().map(|t| format!("{:.3}", t.position.re)),
+            Self::PositionY => transform().map(|t| format!("{:.3}", t.position.im)),
+            Self::VelocityX => movement().map(|m| format!("{:.3}", m.velocity.re)),
+            Self::VelocityY => movement().map(|m| format!("{:.3}", m.velocity.im)),
+            Self::Hitpoint => hitpoint().map(|hp| format!("{:.1}", hp.hp)),
+            Self::Invulnerable => hitpoint().map(|hp| hp.invulnerable.to_string()),
+        }
+    }
+
+    /// Nudge the value up or down, booleans are toggled either way.
+    fn edit(self, world: &World, entity: Entity, sign: f32) {
+        match self {
+            Self::PositionX | Self::PositionY => {
+                if let Ok(mut transform) = world.get::<&mut Transform2D>(entity) {
+                    let step = sign * POSITION_STEP;
+                    transform.position += if self == Self::PositionX {
+                        cmpx!(step, 0.)
+                    } else {
+                        cmpx!(0., step)
+                    };
+                }
+            }
+            Self::VelocityX | Self::VelocityY => {
+                if let Ok(mut movement) = world.get::<&mut MoveParams>(entity) {
+                    let step = sign * VELOCITY_STEP;
+                    movement.velocity += if self == Self::VelocityX {
+                        cmpx!(step, 0.)
+                    } else {
+                        cmpx!(0., step)
+                    };
+                }
+            }
+            Self::Hitpoint => {
+                if let Ok(mut hp) = world.get::<&mut Hitpoint>(entity) {
+                    hp.hp = sign.mul_add(HITPOINT_STEP, hp.hp).max(0.);
+                    hp.max_hp = hp.max_hp.max(hp.hp);
+                }
+            }
+            Self::Invulnerable => {
+                if let Ok(mut hp) = world.get::<&mut Hitpoint>(entity) {
+                    hp.invulnerable = !hp.invulnerable;
+                }
+            }
+        }
+    }
+}
+
+/// Select an entity with Tab or a click on the stage, show its components and
+/// edit, freeze or despawn it, toggled with F4 in debug builds.
+#[derive(Debug, Default)]
+pub struct Inspector {
+    pub visible: bool,
+    selected: Option<Entity>,
+    field: usize,
+}
+
+impl Inspector {
+    /// `stage` is where the stage is drawn on the window, to pick entities with the mouse.
+    pub fn update(&mut self, world: &mut World, stage: Rect) {
+        if is_key_pressed(TOGGLE_KEY) {
+            self.visible = !self.visible;
+        }
+        if !self.visible {
+            return;
+        }
+
+        self.selected = self.selected.filter(|entity| world.contains(*entity));
+        if is_key_pressed(NEXT_KEY) {
+            self.selected = next_entity(world, self.selected);
+        }
+        if is_mouse_button_pressed(MouseButton::Left) {
+            let (x, y) = mouse_position();
+            let point = cmpx!((x - stage.x) / stage.w, (y - stage.y) / stage.h);
+            self.selected = pick_entity(world, point).or(self.selected);
+        }
+
+        let Some(entity) = self.selected else {
+            return;
+        };
+
+        if is_key_pressed(FIELD_KEYS.0) {
+            self.field = (self.field + FIELDS.len() - 1) % FIELDS.len();
+        }
+        if is_key_pressed(FIELD_KEYS.1) {
+            self.field = (self.field + 1) % FIELDS.len();
+        }
+        if is_key_pressed(EDIT_KEYS.0) {
+            FIELDS[self.field].edit(world, entity, -1.);
+        }
+        if is_key_pressed(EDIT_KEYS.1) {
+            FIELDS[self.field].edit(world, entity, 1.);
+        }
+
+        if is_key_pressed(FREEZE_KEY) {
+            if world.get::<&Frozen>(entity).is_ok() {
+                let _ = world.remove_one::<Frozen>(entity);
+            } else {
+                let _ = world.insert_one(entity, Frozen);
+            }
+        }
+        if is_key_pressed(DESPAWN_KEY) {
+            let _ = world.despawn(entity);
+            self.selected = None;
+        }
+    }
+
+    pub fn draw(&self, world: &World, commands: &mut DrawList) {
+        if !self.visible {
+            return;
+        }
+
+        let Some(entity) = self.selected.filter(|entity| world.contains(*entity)) else {
+            draw_lines(
+                [String::from("inspector: Tab or click to select")],
+                commands,
+            );
+            return;
+        };
+
+        if let Ok(transform) = world.get::<&Transform2D>(entity) {
+            commands.circle(
+                Layer::Debug,
+                vec2(transform.position.re, transform.position.im),
+                PICK_RADIUS,
+                MARKER_COLOR,
+            );
+        }
+
+        draw_lines(describe(world, entity, FIELDS[self.field]), commands);
+    }
+}
+
+fn describe(world: &World, entity: Entity, field: Field) -> Vec<String> {
+    let name = world.get::<&Name>(entity).map_or("unnamed", |name| name.0);
+    let frozen = if world.get::<&Frozen>(entity).is_ok() {
+        " (frozen)"
+    } else {
+        ""
+    };
+    let mut lines = vec![format!("#{} {name}{frozen}", entity.id())];
+
+    if let Ok(transform) = world.get::<&Transform2D>(entity) {
+        lines.push(format!(
+            "position {:.3}, {:.3} | scale {:.3} | rotation {:.1}",
+            transform.position.re, transform.position.im, transform.scale.x, transform.rotation
+        ));
+    }
+    if let Ok(movement) = world.get::<&MoveParams>(entity) {
+        lines.push(format!(
+            "velocity {:.3}, {:.3} | acceleration {:.3}, {:.3} | retention {:.2}",
+            movement.velocity.re,
+            movement.velocity.im,
+            movement.acceleration.re,
+            movement.acceleration.im,
+            movement.retention
+        ));
+    }
+    if let Ok(moves) = world.get::<&Moves>(entity) {
+        lines.push(format!("moves {} left", moves.0.len()));
+        lines.extend(
+            moves
+                .0
+                .iter()
+                .map(|movement| format!("  {} {:.2}s", movement.name(), movement.remaining())),
+        );
+    }
+    if let Ok(attack) = world.get::<&AttackMove>(entity) {
+        attack_cooldowns(&attack, &mut lines);
+    }
+    if let Ok(boss) = world.get::<&BossMoves>(entity) {
+        lines.push(format!("boss phases {} left", boss.0.len()));
+        if let Some(phase) = boss.0.front() {
+            lines.push(format!("  timeout {:.2}s", phase.timeout.remaining()));
+            attack_cooldowns(&phase.attack, &mut lines);
+        }
+    }
+    if let Ok(hp) = world.get::<&Hitpoint>(entity) {
+        lines.push(format!(
+            "hp {:.1}/{:.1}{}",
+            hp.hp,
+            hp.max_hp,
+            if hp.invulnerable { " invulnerable" } else { "" }
+        ));
+    }
+
+    lines.push(format!(
+        "edit {} = {} | [ ] field, - = change, F freeze, Del despawn",
+        field.name(),
+        field
+            .value(world, entity)
+            .unwrap_or_else(|| String::from("-"))
+    ));
+
+    lines
+}
+
+fn attack_cooldowns(attack: &AttackMove, lines: &mut Vec<String>) {
+    match attack {
+        AttackMove::AtPlayer { cooldown, .. } => {
+            lines.push(format!(
+                "  at_player cooldown {:.2}s",
+                cooldown.0.remaining()
+            ));
+        }
+        AttackMove::Circle { cooldown, .. } => {
+            lines.push(format!("  circle cooldown {:.2}s", cooldown.0.remaining()));
+        }
+        AttackMove::Multiple(attacks) => {
+            for attack in attacks {
+                attack_cooldowns(attack, lines);
+            }
+        }
+    }
+}
+
+fn draw_lines(lines: impl IntoIterator<Item = String>, commands: &mut DrawList) {
+    for (line, y) in lines.into_iter().zip(float_iter(0.55, 1., LINE_SPACING)) {
+        commands.text(
+            Layer::Debug,
+            &Text::Left(line),
+            &Transform2D::new(cmpx!(0.02, y), vec2!(TEXT_SIZE), 0.),
+            TEXT_COLOR,
+        );
+    }
+}
+
+/// The entity with a transform after `current`, wrapping around.
+fn next_entity(world: &World, current: Option<Entity>) -> Option<Entity> {
+    let mut entities = world
+        .query::<&Transform2D>()
+        .iter()
+        .map(|(entity, _)| entity)
+        .collect::<Vec<_>>();
+    entities.sort_by_key(|entity| entity.id());
+
+    current
+        .and_then(|current| {
+            entities
+                .iter()
+                .find(|entity| entity.id() > current.id())
+                .copied()
+        })
+        .or_else(|| entities.first().copied())
+}
+
+fn pick_entity(world: &World, point: Complex<f32>) -> Option<Entity> {
+    world
+        .query::<&Transform2D>()
+        .iter()
+        .map(|(entity, transform)| (entity, (transform.position - point).norm()))
+        .filter(|(_, distance)| *distance < PICK_RADIUS)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(entity, _)| entity)
+}
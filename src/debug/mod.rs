@@ -0,0 +1,7 @@
+mod console;
+mod inspector;
+mod overlay;
+
+pub use console::{arg, toggle_arg, Command, CommandRegistry, Console};
+pub use inspector::Inspector;
+pub use overlay::DebugOverlay;
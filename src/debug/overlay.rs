@@ -0,0 +1,119 @@
+use hecs::World;
+use macroquad::prelude::*;
+
+use crate::{
+    bullet::BulletPool,
+    cmpx,
+    components::{Boss, BossMoves, Bullet, Enemy, Hitpoint, Layer, Player, Text, Transform2D},
+    particle::ParticlePool,
+    renderer::DrawList,
+    score::Score,
+    spawner::Spawner,
+    system::draw_hitbox_system,
+    utils::float_iter,
+    vec2,
+};
+
+const TOGGLE_KEY: KeyCode = KeyCode::F3;
+const TEXT_SIZE: f32 = 0.018;
+const LINE_SPACING: f32 = 0.022;
+const TEXT_COLOR: Color = GREEN;
+const BULLET_HITBOX: Color = Color::new(1., 0., 1., 0.8);
+
+/// Hitboxes, entity counts, spawner and boss state drawn over the stage,
+/// toggled with F3 in debug builds.
+#[derive(Debug, Default)]
+pub struct DebugOverlay {
+    pub visible: bool,
+}
+
+impl DebugOverlay {
+    pub fn update(&mut self) {
+        if is_key_pressed(TOGGLE_KEY) {
+            self.visible = !self.visible;
+        }
+    }
+
+    pub fn draw(
+        &self,
+        world: &World,
+        bullets: &BulletPool,
+        particles: &ParticlePool,
+        spawner: &Spawner,
+        score: &Score,
+        commands: &mut DrawList,
+    ) {
+        if !self.visible {
+            return;
+        }
+
+        draw_hitbox_system(world, commands);
+        for (transform, hitbox) in bullets.hitboxes() {
+            commands.polygon(
+                Layer::Debug,
+                hitbox.shape.outline(&transform, 12),
+                BULLET_HITBOX,
+            );
+        }
+
+        let enemies = world.query::<&Enemy>().without::<&Bullet>().iter().count();
+        let player_bullets = world.query::<(&Player, &Bullet)>().iter().count();
+        let (pending, next) = spawner.pending();
+
+        let lines = [
+            format!(
+                "enemies {enemies} | enemy bullets {} | player bullets {player_bullets}",
+                bullets.len()
+            ),
+            format!("entities {} | particles {}", world.len(), particles.len()),
+            format!(
+                "spawner {:.2}s | pending {pending} | next {}",
+                spawner.timer,
+                next.map_or_else(|| String::from("-"), |next| format!("{next:.2}s"))
+            ),
+            boss_status(world),
+            format!(
+                "lives {} | points {} | graze {}",
+                score.lives, score.points, score.graze
+            ),
+        ];
+
+        for (line, y) in lines.into_iter().zip(float_iter(0.03, 1., LINE_SPACING)) {
+            commands.text(
+                Layer::Debug,
+                &Text::Left(line),
+                &Transform2D::new(cmpx!(0.02, y), vec2!(TEXT_SIZE), 0.),
+                TEXT_COLOR,
+            );
+        }
+    }
+
+    /// Frame time graph, drawn on the window once the stage is finalized.
+    pub fn draw_profiler(&self) {
+        if self.visible {
+            macroquad_profiler::profiler(macroquad_profiler::ProfilerParams {
+                fps_counter_pos: vec2(10., 10.),
+            });
+        }
+    }
+}
+
+fn boss_status(world: &World) -> String {
+    let mut query = world.query::<(&Boss, &BossMoves, &Hitpoint)>();
+    let Some((_, (_, moves, hp))) = query.iter().next() else {
+        return String::from("boss -");
+    };
+
+    let Some(phase) = moves.0.front() else {
+        return String::from("boss no phase left");
+    };
+
+    format!(
+        "boss phase {} left | {:.1}s | hp {:.1}/{:.1}{}",
+        moves.0.len(),
+        phase.timeout.remaining(),
+        hp.hp,
+        hp.max_hp,
+        if hp.invulnerable { " invulnerable" } else { "" }
+    )
+}
@@ -1,18 +1,26 @@
+use crate::animation::{AnimationState, Animator, Clip, PlayMode};
+use crate::atlas::SpriteAtlas;
+use crate::bullet::BulletPool;
 use crate::cmpx;
+use crate::collision::{Collider, CollisionLayer};
 use crate::components::*;
 use crate::konst::VIRTUAL_STAGE_ASPECT_RATIO;
 use crate::vec2;
 use crate::{rect, time::Timer};
-use hecs::World;
+use hecs::{DynamicBundle, Entity, EntityBuilder, World};
 use macroquad::prelude::*;
 use num_complex::Complex;
 
-pub fn create_boss_1() -> impl FnOnce(&mut World) {
-    |world| {
-        create_boss(
+pub fn create_boss_1(sprites: &SpriteAtlas) -> impl FnOnce(&mut World) {
+    let sprite = sprites.get("boss");
+    let kunai = sprites.get("bullet_kunai");
+    let animator = enemy_animator(sprite, sprites.get("boss_attack"));
+
+    move |world| {
+        let boss = create_boss(
             world,
             Transform2D::new(cmpx!(0.5, -0.02), vec2!(0.1), 0.),
-            Sprite::new_from_index(2, 0),
+            sprite,
             MoveParams::move_linear(cmpx!(0., 0.2)),
             Moves::new(vec![
                 Movement::new(0.1, Move::MoveDampen(cmpx!(0., 0.8), 0.6)),
@@ -32,13 +40,15 @@ pub fn create_boss_1() -> impl FnOnce(&mut World) {
                             spread: 20.,
                             total_shoot: 2,
                             cooldown: Cooldown::new(1.),
-                            setup: BulletSetup(Sprite::new_from_index(0, 1)),
+                            setup: BulletSetup::new(kunai, Hitbox::capsule(0.006, 0.03)),
                         },
                         AttackMove::Circle {
                             sides: 16,
                             rotation: 12.,
                             rotation_per_fire: 6.,
-                            setup: BulletSetup(Sprite::new_from_index(0, 1)),
+                            setup: BulletSetup::new(kunai, Hitbox::ellipse(0.012, 0.03))
+                                .with_tint(SKYBLUE)
+                                .with_blend(BlendMode::Additive),
                             cooldown: Cooldown::new(1.),
                         },
                     ]),
@@ -52,17 +62,48 @@ pub fn create_boss_1() -> impl FnOnce(&mut World) {
                         spread: 20.,
                         total_shoot: 2,
                         cooldown: Cooldown::new(1.),
-                        setup: BulletSetup(Sprite::new_from_index(0, 1)),
+                        setup: BulletSetup::new(kunai, Hitbox::capsule(0.006, 0.03)),
                     }]),
                 ),
             ]),
-            Hitbox::new(0.05 * VIRTUAL_STAGE_ASPECT_RATIO),
+            Hitbox::circle(0.05 * VIRTUAL_STAGE_ASPECT_RATIO),
             Hitpoint::invulnerable(),
             Cooldown::new(1.),
         );
+        let _ = world.insert_one(boss, animator);
+        #[cfg(debug_assertions)]
+        let _ = world.insert_one(boss, Name("boss_1"));
     }
 }
 
+// INFO : Attack frame of an enemy also flicker when it get hit
+fn enemy_animator(idle: Sprite, attack: Sprite) -> Animator {
+    Animator::new(Clip::still(idle))
+        .with(
+            AnimationState::Hit,
+            Clip::new(PlayMode::PingPong)
+                .frame(attack, 0.04)
+                .frame(idle, 0.04)
+                .frame(attack, 0.04),
+        )
+        .with(
+            AnimationState::Attack,
+            Clip::new(PlayMode::Once).frame(attack, 0.15),
+        )
+}
+
+fn player_animator(sprites: &SpriteAtlas) -> Animator {
+    Animator::new(Clip::still(sprites.get("player")))
+        .with(
+            AnimationState::BankLeft,
+            Clip::still(sprites.get("player_bank_left")),
+        )
+        .with(
+            AnimationState::BankRight,
+            Clip::still(sprites.get("player_bank_right")),
+        )
+}
+
 pub fn create_boss(
     world: &mut World,
     transform: Transform2D,
@@ -73,7 +114,7 @@ pub fn create_boss(
     hitbox: Hitbox,
     hitpoint: Hitpoint,
     cooldown: Cooldown,
-) {
+) -> Entity {
     world.spawn((
         Enemy,
         Boss,
@@ -88,15 +129,24 @@ pub fn create_boss(
         cooldown,
         HealthBar(Rect::new(0.04, 0.02, 0.9, 0.01)),
         Wanderable::new(transform.position),
-    ));
+        Collider::new(CollisionLayer::ENEMY, CollisionLayer::NONE),
+        Layer::Enemy,
+    ))
 }
 
-pub fn create_red_alien_wait(transform: Complex<f32>) -> impl FnOnce(&mut World) {
+pub fn create_red_alien_wait(
+    sprites: &SpriteAtlas,
+    transform: Complex<f32>,
+) -> impl FnOnce(&mut World) {
+    let sprite = sprites.get("red_alien");
+    let bullet = sprites.get("bullet_kunai");
+    let animator = red_alien_animator(sprites);
+
     move |world| {
-        create_enemy(
+        let enemy = create_enemy(
             world,
             Transform2D::new(transform, vec2!(0.1), 0.),
-            Sprite::new_from_index(1, 0),
+            sprite,
             MoveParams::move_linear(cmpx!(0., 0.2)),
             Moves::new(vec![
                 Movement::new(0.1, Move::MoveDampen(cmpx!(0., 0.8), 0.6)),
@@ -108,21 +158,28 @@ pub fn create_red_alien_wait(transform: Complex<f32>) -> impl FnOnce(&mut World)
                 spread: 0.1,
                 speed: 0.5,
                 total_shoot: 1,
-                setup: BulletSetup(Sprite::new_from_index(0, 1)),
+                setup: BulletSetup::new(bullet, Hitbox::capsule(0.006, 0.03)),
                 cooldown: Cooldown::new(1.),
             },
-            Hitbox::new(0.03 * VIRTUAL_STAGE_ASPECT_RATIO),
+            Hitbox::circle(0.03 * VIRTUAL_STAGE_ASPECT_RATIO),
             Hitpoint::new(2.),
         );
+        let _ = world.insert_one(enemy, animator);
+        #[cfg(debug_assertions)]
+        let _ = world.insert_one(enemy, Name("red_alien_wait"));
     }
 }
 
-pub fn create_red_alien(transform: Complex<f32>) -> impl FnOnce(&mut World) {
+pub fn create_red_alien(sprites: &SpriteAtlas, transform: Complex<f32>) -> impl FnOnce(&mut World) {
+    let sprite = sprites.get("red_alien");
+    let bullet = sprites.get("bullet_arrow_small");
+    let animator = red_alien_animator(sprites);
+
     move |world| {
-        create_enemy(
+        let enemy = create_enemy(
             world,
             Transform2D::new(transform, vec2!(0.1), 0.),
-            Sprite::new_from_index(1, 0),
+            sprite,
             MoveParams::move_linear(cmpx!(0., 0.2)),
             Moves::new(vec![
                 Movement::new(0.5, Move::MoveTowardExp(cmpx!(0., 1.), cmpx!(0.1), 0.1)),
@@ -133,15 +190,22 @@ pub fn create_red_alien(transform: Complex<f32>) -> impl FnOnce(&mut World) {
                 spread: 2.,
                 speed: 0.5,
                 total_shoot: 1,
-                setup: BulletSetup(Sprite::new_from_index(1, 1)),
+                setup: BulletSetup::new(bullet, Hitbox::rotated_rect(0.008, 0.024)),
                 cooldown: Cooldown::new(1.),
             },
-            Hitbox::new(0.03 * VIRTUAL_STAGE_ASPECT_RATIO),
+            Hitbox::circle(0.03 * VIRTUAL_STAGE_ASPECT_RATIO),
             Hitpoint::new(2.),
         );
+        let _ = world.insert_one(enemy, animator);
+        #[cfg(debug_assertions)]
+        let _ = world.insert_one(enemy, Name("red_alien"));
     }
 }
 
+fn red_alien_animator(sprites: &SpriteAtlas) -> Animator {
+    enemy_animator(sprites.get("red_alien"), sprites.get("red_alien_attack"))
+}
+
 pub fn create_enemy(
     world: &mut World,
     transform: Transform2D,
@@ -151,7 +215,7 @@ pub fn create_enemy(
     attack_move: AttackMove,
     hitbox: Hitbox,
     hitpoint: Hitpoint,
-) {
+) -> Entity {
     world.spawn((
         Enemy,
         DieOffScreen,
@@ -162,28 +226,27 @@ pub fn create_enemy(
         attack_move,
         moves,
         hitpoint,
-    ));
+        Collider::new(CollisionLayer::ENEMY, CollisionLayer::NONE),
+        Layer::Enemy,
+    ))
 }
 
+// INFO : Enemy bullets live in the pool and only move linearly
 pub fn create_enemy_bullet(
-    world: &mut World,
+    bullets: &mut BulletPool,
     transform: Transform2D,
-    sprite: Sprite,
+    setup: &BulletSetup,
     movement: MoveParams,
-    hitbox: Hitbox,
 ) {
-    world.spawn((
-        Enemy,
-        Bullet,
-        DieOffScreen,
-        movement,
-        transform,
-        sprite,
-        hitbox,
-    ));
+    bullets.spawn(transform, movement.velocity, setup);
 }
 
-pub fn create_player_generic_bullet(pos: Complex<f32>) -> impl FnOnce(&mut World) {
+pub fn create_player_generic_bullet(
+    sprites: &SpriteAtlas,
+    pos: Complex<f32>,
+) -> impl FnOnce(&mut World) {
+    let sprite = sprites.get("bullet_kunai");
+
     move |world| {
         create_player_bullet(
             world,
@@ -192,9 +255,9 @@ pub fn create_player_generic_bullet(pos: Complex<f32>) -> impl FnOnce(&mut World
                 scale: vec2!(0.05),
                 rotation: 0.,
             },
-            Sprite::new_from_index(0, 1),
+            sprite,
             MoveParams::move_linear(cmpx!(0., -2.0)),
-            Hitbox::new(0.01 * VIRTUAL_STAGE_ASPECT_RATIO),
+            Hitbox::rect(0.016, 0.03),
         )
     }
 }
@@ -206,40 +269,213 @@ pub fn create_player_bullet(
     movement: MoveParams,
     hitbox: Hitbox,
 ) {
-    world.spawn((
-        Player,
-        Bullet,
-        DieOffScreen,
-        movement,
-        transform,
-        sprite,
-        hitbox,
-    ));
+    spawn_named(
+        world,
+        "player_bullet",
+        (
+            Player,
+            Bullet,
+            DieOffScreen,
+            movement,
+            transform,
+            sprite,
+            hitbox,
+            Damage(0.5),
+            FastMoving::new(transform.position),
+            Collider::new(CollisionLayer::PLAYER_BULLET, CollisionLayer::ENEMY),
+            Layer::PlayerBullet,
+        ),
+    );
 }
 
-pub fn create_player(world: &mut World) {
-    let offset = cmpx!(0.5, 0.8);
+pub fn create_player(sprites: &SpriteAtlas) -> impl FnOnce(&mut World) {
+    let sprite = sprites.get("player");
+    let animator = player_animator(sprites);
 
-    world.spawn((
-        Player,
-        Controllable,
-        Sprite::new_from_index(0, 0),
-        Transform2D::new(offset, vec2!(0.1), 0.),
-        MoveParams::move_dampen(cmpx!(0.), 0.85),
-        Cooldown(Timer::new(0.1, true)),
-        Hitbox::new(0.0125 * VIRTUAL_STAGE_ASPECT_RATIO),
-    ));
+    move |world| {
+        let offset = cmpx!(0.5, 0.8);
+
+        spawn_named(
+            world,
+            "player",
+            (
+                Player,
+                Controllable,
+                sprite,
+                Transform2D::new(offset, vec2!(0.1), 0.),
+                MoveParams::move_dampen(cmpx!(0.), 0.85),
+                Cooldown(Timer::new(0.1, true)),
+                Hitbox::circle(0.0125 * VIRTUAL_STAGE_ASPECT_RATIO),
+                Collider::new(CollisionLayer::PLAYER, CollisionLayer::ITEM)
+                    .with_near(CollisionLayer::ENEMY_BULLET),
+                Layer::Player,
+                animator,
+            ),
+        );
+    }
+}
+
+pub fn create_background(background: ParallaxBackground) -> impl FnOnce(&mut World) {
+    move |world| {
+        spawn_named(world, "background", (background, Tint(WHITE)));
+    }
+}
+
+/// Fade every background out and `background` in over `duration` seconds.
+pub fn crossfade_background(
+    background: ParallaxBackground,
+    duration: f32,
+) -> impl FnOnce(&mut World) {
+    move |world| {
+        let current = world
+            .query::<(&ParallaxBackground, Option<&Tint>)>()
+            .iter()
+            .map(|(id, (_, tint))| (id, tint.map_or(1., |tint| tint.0.a)))
+            .collect::<Vec<_>>();
+
+        for (id, alpha) in current {
+            let _ = world.insert(
+                id,
+                (
+                    Fade::new(duration, alpha, 0.),
+                    Tint(Color { a: alpha, ..WHITE }),
+                ),
+            );
+        }
+
+        spawn_named(
+            world,
+            "background",
+            (
+                background,
+                Tint(Color { a: 0., ..WHITE }),
+                Fade::fade_in(duration),
+            ),
+        );
+    }
+}
+
+pub fn background_speed(speed: f32, duration: f32) -> impl FnOnce(&mut World) {
+    move |world| {
+        world
+            .query::<&mut ParallaxBackground>()
+            .iter()
+            .for_each(|(_, background)| background.set_speed(speed, duration));
+    }
+}
+
+/// Crossfade the music to `track` over `fade` seconds.
+pub fn play_music(track: &str, fade: f32) -> impl FnOnce(&mut World) {
+    let track = track.to_string();
+
+    move |world| {
+        spawn_named(
+            world,
+            "music_request",
+            (MusicRequest::Play { track, fade },),
+        );
+    }
+}
+
+/// Point item popping out of a killed enemy then falling, the player pick it up on contact.
+pub fn create_point_item(sprites: &SpriteAtlas, position: Complex<f32>) -> impl FnOnce(&mut World) {
+    let sprite = sprites.get("item_point");
+
+    move |world| {
+        spawn_named(
+            world,
+            "point_item",
+            (
+                Item::Point(100),
+                DieOffScreen,
+                Transform2D::new(position, vec2!(0.03), 0.),
+                sprite,
+                MoveParams::move_accelerated(cmpx!(0., -0.3), cmpx!(0., 0.8)),
+                Hitbox::circle(0.02 * VIRTUAL_STAGE_ASPECT_RATIO),
+                Collider::new(CollisionLayer::ITEM, CollisionLayer::NONE),
+                Layer::Item,
+            ),
+        );
+    }
+}
+
+/// Number drifting up and fading out, for damage dealt or points scored.
+pub fn create_floating_number(
+    value: &impl ToString,
+    position: Complex<f32>,
+    color: Color,
+) -> impl FnOnce(&mut World) {
+    let text = value.to_string();
+
+    move |world| {
+        spawn_named(
+            world,
+            "floating_number",
+            (
+                Transform2D::new(position, vec2!(0.025), 0.),
+                Text::Center(text),
+                Tint(color),
+                Fade::fade_out(0.6),
+                MoveParams::move_dampen(cmpx!(0., -0.3), 0.9),
+            ),
+        );
+    }
 }
 
 pub fn stage_text(text: &str) -> impl FnOnce(&mut World) {
     let text = text.to_string();
 
     move |world| {
-        world.spawn((
-            DieOffScreen,
-            Transform2D::new(cmpx!(0.5, 0.3), vec2!(0.05), 0.),
-            Text::Center(text),
-            // MoveParams::move_accelerated(cmpx!(0.), cmpx!(0., -0.1)),
-        ));
+        spawn_named(
+            world,
+            "stage_text",
+            (
+                DieOffScreen,
+                Transform2D::new(cmpx!(0.5, 0.3), vec2!(0.05), 0.),
+                Text::Center(text),
+                // MoveParams::move_accelerated(cmpx!(0.), cmpx!(0., -0.1)),
+            ),
+        );
     }
 }
+
+/// Spawn `components` with a [`Name`] for the debug tools, release builds leave the name out.
+#[cfg_attr(not(debug_assertions), allow(unused_variables))]
+fn spawn_named(world: &mut World, name: &'static str, components: impl DynamicBundle) {
+    let mut builder = EntityBuilder::new();
+    builder.add_bundle(components);
+    #[cfg(debug_assertions)]
+    builder.add(Name(name));
+    world.spawn(builder.build());
+}
+
+#[cfg(debug_assertions)]
+pub fn register_commands(registry: &mut crate::debug::CommandRegistry) {
+    use crate::debug::{arg, Command};
+
+    registry.register(Command {
+        name: "spawn",
+        usage: "spawn <red_alien|red_alien_wait|boss_1> [x y]",
+        completions: &[&["red_alien", "red_alien_wait", "boss_1"]],
+        run: |game, args| {
+            let name = args.first().ok_or("missing entity")?;
+            let position = if args.len() > 1 {
+                cmpx!(arg(args, 1, "x")?, arg(args, 2, "y")?)
+            } else {
+                cmpx!(0.5, 0.1)
+            };
+
+            let sprites = &game.resources.sprites;
+            let spawn: Box<dyn FnOnce(&mut World)> = match *name {
+                "red_alien" => Box::new(create_red_alien(sprites, position)),
+                "red_alien_wait" => Box::new(create_red_alien_wait(sprites, position)),
+                // INFO : The boss always enter from the top
+                "boss_1" => Box::new(create_boss_1(sprites)),
+                _ => return Err(format!("unknown entity `{name}`")),
+            };
+            spawn(&mut game.world);
+
+            Ok(format!("spawned {name}"))
+        },
+    });
+}
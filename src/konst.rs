@@ -2,6 +2,9 @@ pub const VIRTUAL_SCREEN_WIDTH: u32 = 1280;
 
 pub const VIRTUAL_STAGE_WIDTH: u32 = 1118;
 pub const VIRTUAL_STAGE_HEIGHT: u32 = 1366;
+// INFO : Stage resolution used by integer scaling, about a fifth of the virtual stage
+pub const PIXEL_STAGE_WIDTH: u32 = 224;
+pub const PIXEL_STAGE_HEIGHT: u32 = 274;
 
 pub const VIRTUAL_STAGE_ASPECT_RATIO: f32 =
     VIRTUAL_STAGE_WIDTH as f32 / VIRTUAL_STAGE_HEIGHT as f32;
@@ -1,21 +1,43 @@
+use audio::Audio;
+use bullet::BulletPool;
+use collision::SpatialGrid;
 use controls::*;
+use cue::Cues;
 use hecs::World;
-use macroquad::prelude::*;
-use renderer::Renderer;
+use macroquad::{miniquad::conf::Platform, prelude::*};
+use menu::{Menu, MenuAction, OptionsMenu, PauseMenu};
+use particle::{ParticleEffects, ParticlePool};
+use renderer::{draw_error_screen, DrawList, EffectOptions, Renderer};
 use resources::Resources;
+use score::Score;
+use settings::Settings;
 use spawner::Spawner;
 use stage::*;
 use system::*;
 use utils::FPSCounter;
 
+mod animation;
+mod atlas;
+mod audio;
+mod bullet;
+mod collision;
 mod components;
 mod controls;
+mod cue;
+#[cfg(debug_assertions)]
+mod debug;
 mod entity;
 mod konst;
 mod math;
+mod menu;
+mod pack;
+mod particle;
+#[cfg(feature = "hot-reload")]
+mod reload;
 mod renderer;
 mod resources;
 mod score;
+mod settings;
 mod spawner;
 mod stage;
 mod system;
@@ -25,72 +47,387 @@ mod utils;
 pub struct Game {
     resources: Resources,
     renderer: Renderer,
+    draw_list: DrawList,
     controls: Controls,
     world: World,
+    bullets: BulletPool,
+    particles: ParticlePool,
+    score: Score,
+    cues: Cues,
+    audio: Audio,
+    settings: Settings,
+    menu: Option<Menu>,
+    grid: SpatialGrid,
+    benchmark: Option<CollisionBenchmark>,
 
     fps: FPSCounter,
+    #[cfg(debug_assertions)]
+    overlay: debug::DebugOverlay,
+    #[cfg(debug_assertions)]
+    inspector: debug::Inspector,
+    #[cfg(debug_assertions)]
+    console: debug::Console,
 
+    stage: Stage,
     spawner: Spawner,
+    #[cfg(feature = "hot-reload")]
+    watcher: reload::Watcher,
 }
 
 impl Game {
-    pub async fn new() -> Self {
+    pub async fn new(settings: Settings) -> Self {
         let world = World::new();
-        let controls = init_controls();
+        let controls = init_controls(&settings.bindings);
         let resources = Resources::new().await;
         let renderer = Renderer::default();
         let fps = FPSCounter::default();
+        #[cfg(feature = "audio")]
+        let backend = audio::backend(&resources.sounds, &resources.music).await;
+        // INFO : Nothing to play them with, sounds are only recorded
+        #[cfg(not(feature = "audio"))]
+        let backend = Box::new(audio::RecordingBackend::default());
+        let audio = Audio::new(backend, &resources.sounds, &resources.music);
+        let particles = ParticlePool::new(ParticleEffects::new(&resources.sprites));
 
+        let (stage, benchmark): (Stage, _) = if std::env::args().any(|arg| arg == "--benchmark") {
+            (stage_benchmark, Some(CollisionBenchmark::default()))
+        } else {
+            (stage_1, None)
+        };
         let mut spawner = Spawner::default();
-        stage_1(&mut spawner);
+        stage(&mut spawner, &resources);
 
-        Self {
+        #[cfg(feature = "hot-reload")]
+        let watcher = reload::Watcher::new([
+            resources::asset_root().join(resources::ATLAS_TEXTURE),
+            resources::asset_root().join(resources::ATLAS_METADATA),
+            resources::asset_root().join(resources::STAGE_1),
+        ]);
+
+        let mut game = Self {
             world,
             controls,
             resources,
             renderer,
+            draw_list: DrawList::default(),
+            bullets: BulletPool::default(),
+            particles,
+            score: Score::default(),
+            cues: Cues::default(),
+            audio,
+            settings,
+            menu: None,
+            grid: SpatialGrid::default(),
+            benchmark,
 
             fps,
+            #[cfg(debug_assertions)]
+            overlay: debug::DebugOverlay::default(),
+            #[cfg(debug_assertions)]
+            inspector: debug::Inspector::default(),
+            #[cfg(debug_assertions)]
+            console: debug::Console::new(commands()),
 
+            stage,
             spawner,
+            #[cfg(feature = "hot-reload")]
+            watcher,
+        };
+        game.apply_settings();
+        game
+    }
+
+    /// Push the settings to everything that use them, called again whenever
+    /// the options menu change one.
+    fn apply_settings(&mut self) {
+        self.controls = init_controls(&self.settings.bindings);
+        self.audio.sfx.volume = self.settings.sfx_volume;
+        self.audio.music.volume = self.settings.music_volume;
+        self.renderer.set_display(self.settings.display);
+        self.renderer.effects.options = EffectOptions {
+            shake: self.settings.accessibility.screen_shake,
+            flash: self.settings.accessibility.screen_flash,
+        };
+    }
+
+    fn open_menu(&mut self) {
+        self.menu = Some(Menu::Pause(PauseMenu::default()));
+        self.audio.set_paused(true);
+    }
+
+    fn close_menu(&mut self) {
+        self.menu = None;
+        self.audio.set_paused(false);
+    }
+
+    fn update_menu(&mut self) {
+        let fullscreen = self.settings.window.fullscreen;
+        let action = match &mut self.menu {
+            Some(Menu::Pause(pause)) => pause.update(&self.controls),
+            Some(Menu::Options(options)) => options.update(&self.controls, &mut self.settings),
+            None => MenuAction::None,
+        };
+
+        match action {
+            MenuAction::None => {}
+            MenuAction::Resume => self.close_menu(),
+            MenuAction::Restart => {
+                self.close_menu();
+                self.restart_stage();
+            }
+            MenuAction::Options => self.menu = Some(Menu::Options(OptionsMenu::default())),
+            MenuAction::Changed => {
+                // INFO : The window already start with it, only toggle on change
+                if self.settings.window.fullscreen != fullscreen {
+                    set_fullscreen(self.settings.window.fullscreen);
+                }
+                self.apply_settings();
+            }
+            MenuAction::Back => {
+                if let Err(err) = self.settings.save() {
+                    eprintln!("settings: {err}");
+                }
+                self.menu = Some(Menu::Pause(PauseMenu::default()));
+            }
+        }
+    }
+
+    /// Run one console line and print what it returned.
+    #[cfg(debug_assertions)]
+    fn run_command(&mut self, line: &str) {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        let Some((name, args)) = words.split_first() else {
+            return;
+        };
+
+        let Some((run, usage)) = self
+            .console
+            .registry
+            .get(name)
+            .map(|command| (command.run, command.usage))
+        else {
+            self.console
+                .print(format!("unknown command `{name}`, try `help`"), RED);
+            return;
+        };
+
+        match run(self, args) {
+            Ok(message) => {
+                for line in message.lines() {
+                    self.console.print(line, WHITE);
+                }
+            }
+            Err(err) => {
+                self.console.print(err, RED);
+                self.console.print(format!("usage: {usage}"), GRAY);
+            }
+        }
+    }
+
+    /// Clear the stage and queue every spawn of it again.
+    pub fn restart_stage(&mut self) {
+        self.world.clear();
+        self.bullets.clear();
+        self.particles.clear();
+        self.spawner = Spawner::default();
+        (self.stage)(&mut self.spawner, &self.resources);
+    }
+
+    /// Reload the assets and restart the boss phase in place, or the whole
+    /// stage when there is no boss or the stage file `changed`, so the new
+    /// data is used right away.
+    #[cfg(feature = "hot-reload")]
+    async fn hot_reload(&mut self, changed: &[std::path::PathBuf]) {
+        let resources = Resources::new().await;
+        for error in &resources.errors {
+            eprintln!("asset error: {error}");
+        }
+        // INFO : Keep playing with the old assets until the broken one is fixed, a
+        // renamed sprite is an error too since the stage and particles look them up
+        if !resources.errors.is_empty() {
+            return;
+        }
+
+        let old = std::mem::replace(&mut self.resources, resources);
+        self.particles = ParticlePool::new(ParticleEffects::new(&self.resources.sprites));
+        let stage_changed = changed
+            .iter()
+            .any(|path| path.ends_with(resources::STAGE_1));
+        if !stage_changed
+            && reload::restart_boss_phase(&self.world, &old.sprites, &self.resources.sprites)
+        {
+            self.bullets.clear();
+        } else {
+            self.restart_stage();
         }
     }
 
     pub fn update(&mut self) {
-        let delta = get_frame_time();
+        let delta = time::delta_time();
+        let bullet_speed = self.settings.difficulty.bullet_speed();
 
         self.fps.update();
+        #[cfg(debug_assertions)]
+        {
+            // INFO : The game wait while typing, Escape closing it shouldn't open the menu
+            let typing = self.console.open;
+            if let Some(line) = self.console.update() {
+                self.run_command(&line);
+            }
+            if typing || self.console.open {
+                return;
+            }
+
+            self.overlay.update();
+            self.inspector
+                .update(&mut self.world, self.renderer.stage_rect());
+        }
+        if self.menu.is_some() {
+            self.update_menu();
+            return;
+        }
+        if self.controls.is_pressed(Action::Escape) {
+            self.open_menu();
+            return;
+        }
+
+        self.cues.clear();
+        self.renderer.effects.update(delta);
         self.spawner.update(&mut self.world, delta);
-        player_controls(&mut self.world, &self.controls);
-        update_boss_move(&mut self.world);
+        player_controls(
+            &mut self.world,
+            &self.controls,
+            &self.resources.sprites,
+            &mut self.cues,
+        );
+        update_boss_move(
+            &self.world,
+            &mut self.bullets,
+            &mut self.particles,
+            &mut self.renderer.effects,
+            bullet_speed,
+        );
         update_moves(&self.world);
         update_movement(&self.world);
-        fire_bullets(&mut self.world);
-        collision(&mut self.world);
+        update_fade(&self.world);
+        update_hit_flash(&mut self.world);
+        update_parallax(&self.world, delta);
+        update_animation(&self.world, delta);
+        self.bullets.update(delta);
+        self.particles.update(delta);
+        fire_bullets(&self.world, &mut self.bullets, bullet_speed);
+        let (events, bullet_events) = match &mut self.benchmark {
+            Some(benchmark) => benchmark.measure(&self.world, &mut self.grid, &self.bullets),
+            None => (
+                collision(&self.world, &mut self.grid),
+                self.bullets.collide(&self.world, &mut self.grid),
+            ),
+        };
+        damage_system(
+            &mut self.world,
+            &events,
+            &self.resources.sprites,
+            &mut self.particles,
+            &mut self.renderer.effects,
+            &mut self.cues,
+            self.settings.accessibility.damage_numbers,
+        );
+        graze_system(
+            &mut self.world,
+            &events,
+            &mut self.score,
+            &mut self.particles,
+            &mut self.cues,
+        );
+        pickup_system(
+            &mut self.world,
+            &events,
+            &mut self.score,
+            &mut self.cues,
+            self.settings.accessibility.damage_numbers,
+        );
+        bullet_hit_system(
+            &mut self.world,
+            &mut self.bullets,
+            &bullet_events,
+            &mut self.renderer.effects,
+            &mut self.cues,
+        );
+        bullet_graze_system(
+            &mut self.bullets,
+            &bullet_events,
+            &mut self.score,
+            &mut self.particles,
+            &mut self.cues,
+        );
+        clean_faded(&mut self.world);
         scan_been_onscreen(&mut self.world);
         clean_offscreen(&mut self.world);
+
+        music_system(&mut self.world, &mut self.audio);
+        self.audio.play_cues(&self.cues);
+        // INFO : Music keep its pace when gameplay is slowed down
+        self.audio.update(get_frame_time());
     }
 
-    pub fn render(&self) {
-        self.renderer.init();
+    pub fn render(&mut self) {
         let world = &self.world;
-        let renderer = &self.renderer;
-        let fps = &self.fps;
-        let resources = &self.resources;
-
-        draw_sprites_system(world, resources, renderer);
-        draw_text_system(world, resources, renderer);
-        // draw_hitbox_system(world, renderer);
-        draw_boss_hitpoint(world, renderer);
-        self.renderer.finalize(fps, resources);
-
-        // macroquad_profiler::profiler(macroquad_profiler::ProfilerParams {
-        //     fps_counter_pos: vec2!(0., 0.),
-        // });
+        let commands = &mut self.draw_list;
+
+        draw_parallax_system(world, commands);
+        draw_sprites_system(world, commands);
+        draw_bullets_system(&self.bullets, commands);
+        draw_particles_system(&self.particles, commands);
+        draw_player_hitbox_system(world, commands);
+        draw_text_system(world, commands);
+        #[cfg(debug_assertions)]
+        self.overlay.draw(
+            world,
+            &self.bullets,
+            &self.particles,
+            &self.spawner,
+            &self.score,
+            commands,
+        );
+        #[cfg(debug_assertions)]
+        {
+            self.inspector.draw(world, commands);
+            self.console.draw(commands);
+        }
+        draw_boss_hitpoint(world, commands);
+        draw_now_playing(self.audio.music.now_playing(), commands);
+        match &self.menu {
+            Some(Menu::Pause(pause)) => pause.draw(commands),
+            Some(Menu::Options(options)) => options.draw(&self.settings, commands),
+            None => {}
+        }
+
+        self.renderer.init();
+        self.renderer.submit(commands, &self.resources);
+        self.renderer.finalize(&self.fps, &self.resources);
+        #[cfg(debug_assertions)]
+        self.overlay.draw_profiler();
     }
 
     pub async fn run(&mut self) {
+        for error in &self.resources.errors {
+            eprintln!("asset error: {error}");
+        }
+
+        while !self.resources.errors.is_empty() && !self.controls.is_pressed(Action::Accept) {
+            draw_error_screen(&self.resources);
+            next_frame().await;
+        }
+
         loop {
+            #[cfg(feature = "hot-reload")]
+            {
+                let changed = self.watcher.changed();
+                if !changed.is_empty() {
+                    self.hot_reload(&changed).await;
+                }
+            }
+
             self.update();
             self.render();
             next_frame().await;
@@ -98,16 +435,71 @@ impl Game {
     }
 }
 
-pub fn window() -> Conf {
+/// Every console command, each module register the ones for the systems it own.
+#[cfg(debug_assertions)]
+fn commands() -> debug::CommandRegistry {
+    let mut registry = debug::CommandRegistry::default();
+    entity::register_commands(&mut registry);
+    system::register_commands(&mut registry);
+    spawner::register_commands(&mut registry);
+    score::register_commands(&mut registry);
+    time::register_commands(&mut registry);
+
+    registry.register(debug::Command {
+        name: "help",
+        usage: "help",
+        completions: &[],
+        run: |game, _| {
+            Ok(game
+                .console
+                .registry
+                .usages()
+                .collect::<Vec<_>>()
+                .join("\n"))
+        },
+    });
+    registry.register(debug::Command {
+        name: "reload",
+        usage: "reload stage",
+        completions: &[&["stage"]],
+        run: |game, args| {
+            if args.first() != Some(&"stage") {
+                return Err(String::from("expected `stage`"));
+            }
+            game.restart_stage();
+            Ok(String::from("stage restarted"))
+        },
+    });
+
+    registry
+}
+
+/// Load the settings once, open the window with them and run the game.
+pub fn start() {
+    let (settings, warnings) = Settings::load();
+    for warning in &warnings {
+        eprintln!("settings: {warning}");
+    }
+
+    macroquad::Window::from_config(window(&settings), async move {
+        let mut game = Game::new(settings).await;
+        game.run().await;
+    });
+}
+
+fn window(settings: &Settings) -> Conf {
     Conf {
         window_title: String::from("Mininvader"),
-        fullscreen: false,
-        window_width: 800,
-        window_height: 600,
-        window_resizable: false,
+        fullscreen: settings.window.fullscreen,
+        window_width: settings.window.width,
+        window_height: settings.window.height,
+        window_resizable: true,
         icon: None,
         sample_count: 1,
         high_dpi: true,
-        platform: Default::default(),
+        platform: Platform {
+            swap_interval: Some(i32::from(settings.window.vsync)),
+            ..Default::default()
+        },
     }
 }
@@ -1,7 +1,3 @@
-use mininvaders::{window, Game};
-
-#[macroquad::main(window)]
-async fn main() {
-    let mut game = Game::new().await;
-    game.run().await;
+fn main() {
+    mininvaders::start();
 }
@@ -0,0 +1,306 @@
+use macroquad::prelude::*;
+
+use crate::{
+    cmpx,
+    components::{Layer, Text, Transform2D},
+    controls::{is_bindable, key_name, Action, Controls},
+    renderer::{DrawList, ScaleMode},
+    settings::{Difficulty, Settings},
+    utils::float_iter,
+    vec2,
+};
+
+const TITLE_SIZE: f32 = 0.05;
+const ITEM_SIZE: f32 = 0.03;
+const ITEM_SPACING: f32 = 0.035;
+const FIRST_ITEM: f32 = 0.22;
+const SELECTED: Color = YELLOW;
+
+/// What the game should do once a menu handled the input of the frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuAction {
+    None,
+    Resume,
+    Restart,
+    Options,
+    /// A setting was changed and has to be applied.
+    Changed,
+    /// Leave the options, the settings should be saved.
+    Back,
+}
+
+pub enum Menu {
+    Pause(PauseMenu),
+    Options(OptionsMenu),
+}
+
+const PAUSE_ITEMS: [&str; 3] = ["Resume", "Options", "Restart"];
+
+#[derive(Debug, Default)]
+pub struct PauseMenu {
+    selected: usize,
+}
+
+impl PauseMenu {
+    pub fn update(&mut self, controls: &Controls) -> MenuAction {
+        if controls.is_pressed(Action::Escape) {
+            return MenuAction::Resume;
+        }
+
+        self.selected = navigate(controls, self.selected, PAUSE_ITEMS.len());
+        if !controls.is_pressed(Action::Accept) {
+            return MenuAction::None;
+        }
+
+        match self.selected {
+            0 => MenuAction::Resume,
+            1 => MenuAction::Options,
+            _ => MenuAction::Restart,
+        }
+    }
+
+    pub fn draw(&self, commands: &mut DrawList) {
+        draw_background("Paused", commands);
+        for ((i, item), y) in PAUSE_ITEMS.iter().enumerate().zip(item_rows()) {
+            draw_item(item, i == self.selected, y, commands);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OptionItem {
+    Fullscreen,
+    Vsync,
+    ScaleMode,
+    Scanlines,
+    Border,
+    SfxVolume,
+    MusicVolume,
+    Difficulty,
+    ScreenShake,
+    ScreenFlash,
+    DamageNumbers,
+    Key(Action),
+}
+
+const OPTION_ITEMS: [OptionItem; 20] = [
+    OptionItem::Fullscreen,
+    OptionItem::Vsync,
+    OptionItem::ScaleMode,
+    OptionItem::Scanlines,
+    OptionItem::Border,
+    OptionItem::SfxVolume,
+    OptionItem::MusicVolume,
+    OptionItem::Difficulty,
+    OptionItem::ScreenShake,
+    OptionItem::ScreenFlash,
+    OptionItem::DamageNumbers,
+    OptionItem::Key(Action::Attack),
+    OptionItem::Key(Action::Focus),
+    OptionItem::Key(Action::Spell),
+    OptionItem::Key(Action::Up),
+    OptionItem::Key(Action::Left),
+    OptionItem::Key(Action::Right),
+    OptionItem::Key(Action::Down),
+    OptionItem::Key(Action::Escape),
+    OptionItem::Key(Action::Accept),
+];
+
+const VOLUME_STEP: f32 = 0.1;
+
+/// Edit the settings in place, left and right change a value and accept
+/// toggle it or wait for the next key to rebind it.
+#[derive(Debug, Default)]
+pub struct OptionsMenu {
+    selected: usize,
+    rebinding: bool,
+}
+
+impl OptionsMenu {
+    pub fn update(&mut self, controls: &Controls, settings: &mut Settings) -> MenuAction {
+        let item = OPTION_ITEMS[self.selected];
+
+        if self.rebinding {
+            let Some(key) = get_last_key_pressed() else {
+                return MenuAction::None;
+            };
+            self.rebinding = false;
+            return match item {
+                OptionItem::Key(action) if key != KeyCode::Escape && is_bindable(key) => {
+                    settings.rebind(action, key);
+                    MenuAction::Changed
+                }
+                _ => MenuAction::None,
+            };
+        }
+
+        if controls.is_pressed(Action::Escape) {
+            return MenuAction::Back;
+        }
+
+        self.selected = navigate(controls, self.selected, OPTION_ITEMS.len());
+
+        let step = if controls.is_pressed(Action::Right) {
+            1
+        } else if controls.is_pressed(Action::Left) {
+            -1
+        } else if controls.is_pressed(Action::Accept) {
+            0
+        } else {
+            return MenuAction::None;
+        };
+
+        match item {
+            OptionItem::Fullscreen => settings.window.fullscreen = !settings.window.fullscreen,
+            OptionItem::Vsync => settings.window.vsync = !settings.window.vsync,
+            OptionItem::ScaleMode => {
+                settings.display.scale = cycle(&ScaleMode::ALL, settings.display.scale, step);
+            }
+            OptionItem::Scanlines => settings.display.scanlines = !settings.display.scanlines,
+            OptionItem::Border => settings.display.border = !settings.display.border,
+            OptionItem::SfxVolume => settings.sfx_volume = step_volume(settings.sfx_volume, step),
+            OptionItem::MusicVolume => {
+                settings.music_volume = step_volume(settings.music_volume, step);
+            }
+            OptionItem::Difficulty => {
+                settings.difficulty = cycle(&Difficulty::ALL, settings.difficulty, step);
+            }
+            OptionItem::ScreenShake => {
+                settings.accessibility.screen_shake = !settings.accessibility.screen_shake;
+            }
+            OptionItem::ScreenFlash => {
+                settings.accessibility.screen_flash = !settings.accessibility.screen_flash;
+            }
+            OptionItem::DamageNumbers => {
+                settings.accessibility.damage_numbers = !settings.accessibility.damage_numbers;
+            }
+            OptionItem::Key(_) => {
+                self.rebinding = step == 0;
+                return MenuAction::None;
+            }
+        }
+
+        MenuAction::Changed
+    }
+
+    pub fn draw(&self, settings: &Settings, commands: &mut DrawList) {
+        draw_background("Options", commands);
+        for ((i, item), y) in OPTION_ITEMS.iter().enumerate().zip(item_rows()) {
+            let value = match item {
+                OptionItem::Fullscreen => {
+                    format!("Fullscreen: {}", on_off(settings.window.fullscreen))
+                }
+                // INFO : Only read when the window is created
+                OptionItem::Vsync => format!("VSync: {} (restart)", on_off(settings.window.vsync)),
+                OptionItem::ScaleMode => format!("Scaling: {}", settings.display.scale.name()),
+                OptionItem::Scanlines => {
+                    format!("Scanlines: {}", on_off(settings.display.scanlines))
+                }
+                OptionItem::Border => format!("Border: {}", on_off(settings.display.border)),
+                OptionItem::SfxVolume => format!("SFX volume: {:.0}%", settings.sfx_volume * 100.),
+                OptionItem::MusicVolume => {
+                    format!("Music volume: {:.0}%", settings.music_volume * 100.)
+                }
+                OptionItem::Difficulty => format!("Difficulty: {}", settings.difficulty.name()),
+                OptionItem::ScreenShake => {
+                    format!(
+                        "Screen shake: {}",
+                        on_off(settings.accessibility.screen_shake)
+                    )
+                }
+                OptionItem::ScreenFlash => {
+                    format!(
+                        "Screen flash: {}",
+                        on_off(settings.accessibility.screen_flash)
+                    )
+                }
+                OptionItem::DamageNumbers => {
+                    format!(
+                        "Damage numbers: {}",
+                        on_off(settings.accessibility.damage_numbers)
+                    )
+                }
+                OptionItem::Key(action) if self.rebinding && i == self.selected => {
+                    format!("Key {}: press a key", action.name())
+                }
+                OptionItem::Key(action) => format!(
+                    "Key {}: {}",
+                    action.name(),
+                    settings.key(*action).map_or_else(String::new, key_name)
+                ),
+            };
+            draw_item(&value, i == self.selected, y, commands);
+        }
+    }
+}
+
+/// The value after `current` in `all`, or before it when `step` is negative.
+fn cycle<T: Copy + PartialEq>(all: &[T], current: T, step: i32) -> T {
+    let count = all.len();
+    let index = all
+        .iter()
+        .position(|value| *value == current)
+        .unwrap_or_default();
+    let next = if step < 0 {
+        index + count - 1
+    } else {
+        index + 1
+    };
+    all[next % count]
+}
+
+fn step_volume(volume: f32, step: i32) -> f32 {
+    // INFO : Round so repeated steps don't drift away from the 10% marks
+    let step = if step < 0 { -VOLUME_STEP } else { VOLUME_STEP };
+    ((volume + step) * 10.).round().clamp(0., 10.) / 10.
+}
+
+const fn on_off(value: bool) -> &'static str {
+    if value {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+fn navigate(controls: &Controls, selected: usize, count: usize) -> usize {
+    if controls.is_pressed(Action::Up) {
+        (selected + count - 1) % count
+    } else if controls.is_pressed(Action::Down) {
+        (selected + 1) % count
+    } else {
+        selected
+    }
+}
+
+fn draw_background(title: &str, commands: &mut DrawList) {
+    commands.rect(
+        Layer::Hud,
+        Rect::new(0., 0., 1., 1.),
+        Color::new(0., 0., 0., 0.6),
+    );
+    commands.text(
+        Layer::Hud,
+        &Text::Center(title.to_string()),
+        &Transform2D::new(cmpx!(0.5, 0.15), vec2!(TITLE_SIZE), 0.),
+        WHITE,
+    );
+}
+
+fn item_rows() -> impl Iterator<Item = f32> {
+    float_iter(FIRST_ITEM, 1., ITEM_SPACING)
+}
+
+fn draw_item(text: &str, selected: bool, y: f32, commands: &mut DrawList) {
+    let (text, color) = if selected {
+        (format!("> {text}"), SELECTED)
+    } else {
+        (format!("  {text}"), WHITE)
+    };
+    commands.text(
+        Layer::Hud,
+        &Text::Left(text),
+        &Transform2D::new(cmpx!(0.15, y), vec2!(ITEM_SIZE), 0.),
+        color,
+    );
+}
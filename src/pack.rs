@@ -0,0 +1,50 @@
+/// Assets compiled into the binary with the `embed-assets` feature so the game
+/// ship as a single file, a loose file in the asset root take priority over it.
+#[cfg(feature = "embed-assets")]
+const FILES: &[(&str, &[u8])] = &[
+    ("atlas.png", include_bytes!("../assets/atlas.png")),
+    ("atlas.txt", include_bytes!("../assets/atlas.txt")),
+    ("border.png", include_bytes!("../assets/border.png")),
+    ("stage_1.txt", include_bytes!("../assets/stage_1.txt")),
+    (
+        "PressStart2P-Regular.ttf",
+        include_bytes!("../assets/PressStart2P-Regular.ttf"),
+    ),
+    ("sounds.txt", include_bytes!("../assets/sounds.txt")),
+    ("sfx/shot.wav", include_bytes!("../assets/sfx/shot.wav")),
+    ("sfx/hit.wav", include_bytes!("../assets/sfx/hit.wav")),
+    (
+        "sfx/enemy_death.wav",
+        include_bytes!("../assets/sfx/enemy_death.wav"),
+    ),
+    ("sfx/graze.wav", include_bytes!("../assets/sfx/graze.wav")),
+    ("sfx/bomb.wav", include_bytes!("../assets/sfx/bomb.wav")),
+    (
+        "sfx/player_death.wav",
+        include_bytes!("../assets/sfx/player_death.wav"),
+    ),
+    ("sfx/pickup.wav", include_bytes!("../assets/sfx/pickup.wav")),
+    (
+        "sfx/boss_low_hp.wav",
+        include_bytes!("../assets/sfx/boss_low_hp.wav"),
+    ),
+    ("music.txt", include_bytes!("../assets/music.txt")),
+    (
+        "music/stage_1.wav",
+        include_bytes!("../assets/music/stage_1.wav"),
+    ),
+    (
+        "music/boss_1.wav",
+        include_bytes!("../assets/music/boss_1.wav"),
+    ),
+];
+
+#[cfg(not(feature = "embed-assets"))]
+const FILES: &[(&str, &[u8])] = &[];
+
+pub fn get(name: &str) -> Option<&'static [u8]> {
+    FILES
+        .iter()
+        .find(|(file, _)| *file == name)
+        .map(|(_, bytes)| *bytes)
+}
@@ -0,0 +1,366 @@
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use macroquad::prelude::*;
+use num_complex::Complex;
+
+use crate::atlas::SpriteAtlas;
+use crate::components::{BlendMode, Sprite, Transform2D};
+use crate::math::ComplexExt;
+use crate::utils::float_iter;
+
+/// Value over the life of a particle, linear from `start` to `middle` then to `end`.
+#[derive(Debug, Clone, Copy)]
+pub struct Curve {
+    pub start: f32,
+    pub middle: f32,
+    pub end: f32,
+}
+
+impl Curve {
+    pub const fn new(start: f32, middle: f32, end: f32) -> Self {
+        Self { start, middle, end }
+    }
+
+    pub const fn constant(value: f32) -> Self {
+        Self::new(value, value, value)
+    }
+
+    pub const fn linear(start: f32, end: f32) -> Self {
+        Self::new(start, f32::midpoint(start, end), end)
+    }
+
+    /// `t` goes from 0 when the particle spawn to 1 when it dies.
+    pub fn sample(&self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.) * 2.;
+        if t < 1. {
+            (self.middle - self.start).mul_add(t, self.start)
+        } else {
+            (self.end - self.middle).mul_add(t - 1., self.middle)
+        }
+    }
+}
+
+/// How every particle of an [`Emitter`] look and move, ranges are picked at random.
+#[derive(Debug, Clone, Copy)]
+pub struct ParticleSetup {
+    pub sprite: Sprite,
+    pub tint: Color,
+    pub blend: BlendMode,
+    pub lifetime: (f32, f32),
+    pub speed: (f32, f32),
+    /// Angle in radian, particle leave within `spread / 2` of it.
+    pub direction: f32,
+    pub spread: f32,
+    /// Fraction of the velocity lost per second.
+    pub drag: f32,
+    pub scale: Curve,
+    pub alpha: Curve,
+}
+
+impl ParticleSetup {
+    pub const fn new(sprite: Sprite, tint: Color) -> Self {
+        Self {
+            sprite,
+            tint,
+            blend: BlendMode::Additive,
+            lifetime: (0.3, 0.5),
+            speed: (0.1, 0.3),
+            direction: 0.,
+            spread: TAU,
+            drag: 0.,
+            scale: Curve::constant(0.02),
+            alpha: Curve::linear(1., 0.),
+        }
+    }
+
+    pub const fn lifetime(mut self, min: f32, max: f32) -> Self {
+        self.lifetime = (min, max);
+        self
+    }
+
+    pub const fn speed(mut self, min: f32, max: f32) -> Self {
+        self.speed = (min, max);
+        self
+    }
+
+    pub const fn cone(mut self, direction: f32, spread: f32) -> Self {
+        self.direction = direction;
+        self.spread = spread;
+        self
+    }
+
+    pub const fn drag(mut self, drag: f32) -> Self {
+        self.drag = drag;
+        self
+    }
+
+    pub const fn scale(mut self, scale: Curve) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub const fn alpha(mut self, alpha: Curve) -> Self {
+        self.alpha = alpha;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Emission {
+    /// Every particle at once.
+    Burst(usize),
+    /// `rate` particle per second during `duration` seconds.
+    Continuous { rate: f32, duration: f32 },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Emitter {
+    pub setup: ParticleSetup,
+    pub emission: Emission,
+}
+
+impl Emitter {
+    pub const fn burst(setup: ParticleSetup, count: usize) -> Self {
+        Self {
+            setup,
+            emission: Emission::Burst(count),
+        }
+    }
+
+    pub const fn continuous(setup: ParticleSetup, rate: f32, duration: f32) -> Self {
+        Self {
+            setup,
+            emission: Emission::Continuous { rate, duration },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    Explosion,
+    BossExplosion,
+    /// Player bullet hitting an enemy.
+    Impact,
+    Graze,
+    /// Enemy bullet removed by the end of a boss phase.
+    Cancel,
+}
+
+/// Emitters of every [`Effect`], sprites are looked up once like stages do.
+#[derive(Debug)]
+pub struct ParticleEffects {
+    explosion: Vec<Emitter>,
+    boss_explosion: Vec<Emitter>,
+    impact: Vec<Emitter>,
+    graze: Vec<Emitter>,
+    cancel: Vec<Emitter>,
+}
+
+impl ParticleEffects {
+    pub fn new(sprites: &SpriteAtlas) -> Self {
+        let round = sprites.get("particle_round");
+        let spark = sprites.get("particle_spark");
+        let ring = sprites.get("particle_ring");
+
+        let fire = ParticleSetup::new(round, Color::new(1., 0.6, 0.2, 1.))
+            .lifetime(0.3, 0.6)
+            .speed(0.05, 0.25)
+            .drag(3.)
+            .scale(Curve::new(0.03, 0.05, 0.02));
+        let debris = ParticleSetup::new(spark, WHITE)
+            .lifetime(0.2, 0.4)
+            .speed(0.3, 0.6)
+            .drag(2.)
+            .scale(Curve::linear(0.02, 0.005));
+        let shockwave = ParticleSetup::new(ring, WHITE)
+            .lifetime(0.3, 0.3)
+            .speed(0., 0.)
+            .scale(Curve::linear(0.02, 0.15));
+
+        Self {
+            explosion: vec![
+                Emitter::burst(fire, 12),
+                Emitter::burst(debris, 8),
+                Emitter::burst(shockwave, 1),
+            ],
+            boss_explosion: vec![
+                Emitter::burst(fire.speed(0.1, 0.5).scale(Curve::new(0.05, 0.08, 0.03)), 40),
+                Emitter::burst(
+                    shockwave.lifetime(0.6, 0.6).scale(Curve::linear(0.05, 0.6)),
+                    1,
+                ),
+                Emitter::continuous(debris.speed(0.2, 0.8), 80., 0.8),
+            ],
+            impact: vec![Emitter::burst(
+                ParticleSetup::new(spark, Color::new(1., 0.9, 0.5, 1.))
+                    .lifetime(0.1, 0.2)
+                    .speed(0.1, 0.3)
+                    .cone(FRAC_PI_2, 1.5)
+                    .scale(Curve::linear(0.015, 0.005)),
+                4,
+            )],
+            graze: vec![Emitter::burst(
+                ParticleSetup::new(spark, Color::new(0.6, 0.9, 1., 1.))
+                    .lifetime(0.1, 0.25)
+                    .speed(0.2, 0.4)
+                    .drag(4.)
+                    .scale(Curve::linear(0.012, 0.)),
+                3,
+            )],
+            cancel: vec![Emitter::burst(
+                ParticleSetup::new(round, Color::new(0.8, 0.8, 1., 1.))
+                    .lifetime(0.3, 0.4)
+                    .speed(0., 0.05)
+                    .scale(Curve::new(0.03, 0.02, 0.))
+                    .alpha(Curve::new(1., 0.8, 0.)),
+                1,
+            )],
+        }
+    }
+
+    pub fn get(&self, effect: Effect) -> &[Emitter] {
+        match effect {
+            Effect::Explosion => &self.explosion,
+            Effect::BossExplosion => &self.boss_explosion,
+            Effect::Impact => &self.impact,
+            Effect::Graze => &self.graze,
+            Effect::Cancel => &self.cancel,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ActiveEmitter {
+    setup: ParticleSetup,
+    position: Complex<f32>,
+    rate: f32,
+    remaining: f32,
+    pending: f32,
+}
+
+/// Particles live here instead of the [`hecs::World`], like [`crate::bullet::BulletPool`]
+/// every field is in its own array, dead particles are swapped out since
+/// nothing keep an index to them.
+pub struct ParticlePool {
+    pub effects: ParticleEffects,
+    emitters: Vec<ActiveEmitter>,
+    position: Vec<Complex<f32>>,
+    velocity: Vec<Complex<f32>>,
+    age: Vec<f32>,
+    lifetime: Vec<f32>,
+    setup: Vec<ParticleSetup>,
+}
+
+impl ParticlePool {
+    pub const fn new(effects: ParticleEffects) -> Self {
+        Self {
+            effects,
+            emitters: Vec::new(),
+            position: Vec::new(),
+            velocity: Vec::new(),
+            age: Vec::new(),
+            lifetime: Vec::new(),
+            setup: Vec::new(),
+        }
+    }
+
+    pub fn emit(&mut self, effect: Effect, position: Complex<f32>) {
+        for index in 0..self.effects.get(effect).len() {
+            let emitter = self.effects.get(effect)[index];
+            self.start(&emitter, position);
+        }
+    }
+
+    pub fn start(&mut self, emitter: &Emitter, position: Complex<f32>) {
+        match emitter.emission {
+            Emission::Burst(count) => {
+                (0..count).for_each(|_| self.spawn(&emitter.setup, position));
+            }
+            Emission::Continuous { rate, duration } => self.emitters.push(ActiveEmitter {
+                setup: emitter.setup,
+                position,
+                rate,
+                remaining: duration,
+                pending: 0.,
+            }),
+        }
+    }
+
+    fn spawn(&mut self, setup: &ParticleSetup, position: Complex<f32>) {
+        let angle = rand::gen_range(-0.5_f32, 0.5).mul_add(setup.spread, setup.direction);
+        let speed = rand::gen_range(setup.speed.0, setup.speed.1);
+
+        self.position.push(position);
+        self.velocity.push(Complex::from_polar(speed, angle));
+        self.age.push(0.);
+        self.lifetime
+            .push(rand::gen_range(setup.lifetime.0, setup.lifetime.1).max(f32::EPSILON));
+        self.setup.push(*setup);
+    }
+
+    fn remove(&mut self, index: usize) {
+        self.position.swap_remove(index);
+        self.velocity.swap_remove(index);
+        self.age.swap_remove(index);
+        self.lifetime.swap_remove(index);
+        self.setup.swap_remove(index);
+    }
+
+    pub fn clear(&mut self) {
+        self.emitters.clear();
+        (0..self.len()).rev().for_each(|index| self.remove(index));
+    }
+
+    pub const fn len(&self) -> usize {
+        self.age.len()
+    }
+
+    pub fn update(&mut self, delta: f32) {
+        let mut emitters = std::mem::take(&mut self.emitters);
+        for emitter in &mut emitters {
+            let delta = delta.min(emitter.remaining);
+            emitter.remaining -= delta;
+            emitter.pending = emitter.rate.mul_add(delta, emitter.pending);
+
+            let count = emitter.pending.trunc();
+            emitter.pending -= count;
+            for _ in float_iter(0., count, 1.) {
+                self.spawn(&emitter.setup, emitter.position);
+            }
+        }
+        emitters.retain(|emitter| emitter.remaining > 0.);
+        self.emitters = emitters;
+
+        for index in (0..self.len()).rev() {
+            self.age[index] += delta;
+            if self.age[index] >= self.lifetime[index] {
+                self.remove(index);
+                continue;
+            }
+
+            self.velocity[index] *= self.setup[index].drag.mul_add(-delta, 1.).max(0.);
+            self.position[index] += self.velocity[index] * delta;
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Transform2D, &Sprite, Color, BlendMode)> {
+        (0..self.len()).map(|index| {
+            let setup = &self.setup[index];
+            let t = self.age[index] / self.lifetime[index];
+
+            (
+                Transform2D::new(
+                    self.position[index],
+                    Vec2::splat(setup.scale.sample(t)),
+                    self.velocity[index].rot(),
+                ),
+                &setup.sprite,
+                Color {
+                    a: setup.tint.a * setup.alpha.sample(t),
+                    ..setup.tint
+                },
+                setup.blend,
+            )
+        })
+    }
+}
@@ -0,0 +1,123 @@
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use hecs::World;
+
+use crate::{
+    animation::Animator,
+    atlas::SpriteAtlas,
+    components::{AttackMove, Boss, BossMoves, Hitpoint, ParallaxBackground, Sprite},
+    time::Timer,
+};
+
+/// Poll the modification time of a few files, `notify` would be nicer but
+/// checking twice a second is plenty for development.
+pub struct Watcher {
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+    poll: Timer,
+}
+
+impl Watcher {
+    pub fn new(files: impl IntoIterator<Item = PathBuf>) -> Self {
+        Self {
+            files: files
+                .into_iter()
+                .map(|path| {
+                    let modified = modified(&path);
+                    (path, modified)
+                })
+                .collect(),
+            poll: Timer::new(0.5, true),
+        }
+    }
+
+    /// Every file that changed since the last call.
+    pub fn changed(&mut self) -> Vec<PathBuf> {
+        self.poll.update();
+        if !self.poll.completed() {
+            return Vec::new();
+        }
+
+        let mut changed = Vec::new();
+        for (path, last) in &mut self.files {
+            let modified = modified(path);
+            if modified != *last {
+                eprintln!("hot reload: {} changed", path.display());
+                *last = modified;
+                changed.push(path.clone());
+            }
+        }
+
+        changed
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Start the current phase of the boss over with sprites from the `new` atlas,
+/// returns `false` when there is no boss on the stage.
+pub fn restart_boss_phase(world: &World, old: &SpriteAtlas, new: &SpriteAtlas) -> bool {
+    let mut restarted = false;
+
+    let mut bosses = world.query::<(&Boss, &mut BossMoves, &mut Hitpoint)>();
+    for (_, (_, moves, hitpoint)) in &mut bosses {
+        if let Some(phase) = moves.0.front_mut() {
+            phase.timeout.reset();
+            *hitpoint = Hitpoint::new(phase.hp);
+        }
+        restarted = true;
+    }
+    drop(bosses);
+
+    if restarted {
+        remap_sprites(world, old, new);
+    }
+
+    restarted
+}
+
+/// Point every sprite that was looked up in `old` to the same name in `new`.
+fn remap_sprites(world: &World, old: &SpriteAtlas, new: &SpriteAtlas) {
+    for (_, sprite) in &mut world.query::<&mut Sprite>() {
+        *sprite = new.remap(old, *sprite);
+    }
+
+    for (_, animator) in &mut world.query::<&mut Animator>() {
+        animator
+            .sprites_mut()
+            .for_each(|sprite| *sprite = new.remap(old, *sprite));
+    }
+
+    for (_, background) in &mut world.query::<&mut ParallaxBackground>() {
+        for layer in &mut background.layers {
+            layer.sprite = new.remap(old, layer.sprite);
+        }
+    }
+
+    for (_, attack) in &mut world.query::<&mut AttackMove>() {
+        remap_attack(attack, old, new);
+    }
+
+    for (_, moves) in &mut world.query::<&mut BossMoves>() {
+        for phase in &mut moves.0 {
+            remap_attack(&mut phase.attack, old, new);
+        }
+    }
+}
+
+fn remap_attack(attack: &mut AttackMove, old: &SpriteAtlas, new: &SpriteAtlas) {
+    match attack {
+        AttackMove::AtPlayer { setup, .. } | AttackMove::Circle { setup, .. } => {
+            setup.sprite = new.remap(old, setup.sprite);
+        }
+        AttackMove::Multiple(attacks) => attacks
+            .iter_mut()
+            .for_each(|attack| remap_attack(attack, old, new)),
+    }
+}
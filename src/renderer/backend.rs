@@ -0,0 +1,156 @@
+use macroquad::prelude::*;
+
+use crate::components::{BlendMode, Layer, Text, Transform2D};
+
+use super::{DrawCommand, SpriteBatch, StageCamera};
+
+pub trait RenderBackend {
+    fn draw(&mut self, layer: Layer, command: DrawCommand);
+
+    /// Called once every command of the frame has been drawn.
+    fn flush(&mut self) {}
+}
+
+/// Draw with macroquad into the stage, consecutive sprites with the same blend
+/// mode are batched. [`Layer::Background`] is drawn with its own camera.
+pub struct MacroquadBackend<'a> {
+    texture: &'a Texture2D,
+    font: Option<&'a Font>,
+    sprites: &'a mut SpriteBatch,
+    additive: &'a Material,
+    cameras: (&'a StageCamera, &'a StageCamera),
+    blend: BlendMode,
+    background: bool,
+}
+
+impl<'a> MacroquadBackend<'a> {
+    pub const fn new(
+        texture: &'a Texture2D,
+        font: Option<&'a Font>,
+        sprites: &'a mut SpriteBatch,
+        additive: &'a Material,
+        cameras: (&'a StageCamera, &'a StageCamera),
+    ) -> Self {
+        Self {
+            texture,
+            font,
+            sprites,
+            additive,
+            cameras,
+            blend: BlendMode::Normal,
+            background: false,
+        }
+    }
+
+    fn switch_camera(&mut self, layer: Layer) {
+        let background = layer == Layer::Background;
+        if background != self.background {
+            self.flush_sprites();
+            self.background = background;
+            set_camera(if background {
+                self.cameras.1
+            } else {
+                self.cameras.0
+            });
+        }
+    }
+
+    fn flush_sprites(&mut self) {
+        match self.blend {
+            BlendMode::Normal => self.sprites.flush(self.texture),
+            BlendMode::Additive => {
+                gl_use_material(self.additive);
+                self.sprites.flush(self.texture);
+                gl_use_default_material();
+            }
+        }
+    }
+}
+
+impl RenderBackend for MacroquadBackend<'_> {
+    fn draw(&mut self, layer: Layer, command: DrawCommand) {
+        self.switch_camera(layer);
+
+        if let DrawCommand::Sprite(quad) = command {
+            if quad.blend != self.blend {
+                self.flush_sprites();
+                self.blend = quad.blend;
+            }
+            self.sprites.push(quad);
+            return;
+        }
+
+        self.flush_sprites();
+
+        match command {
+            DrawCommand::Sprite(_) => {}
+            DrawCommand::Text {
+                text,
+                transform,
+                color,
+            } => draw_stage_text(&text, self.font, &transform, color),
+            DrawCommand::Rect { rect, color } => {
+                draw_rectangle(rect.x, rect.y, rect.w, rect.h, color);
+            }
+            DrawCommand::Circle {
+                center,
+                radius,
+                color,
+            } => draw_circle(center.x, center.y, radius, color),
+            #[cfg(debug_assertions)]
+            DrawCommand::Polygon { points, color } => {
+                for i in 1..points.len().saturating_sub(1) {
+                    draw_triangle(points[0], points[i], points[i + 1], color);
+                }
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        self.flush_sprites();
+        self.switch_camera(Layer::default());
+    }
+}
+
+fn draw_stage_text(text: &Text, font: Option<&Font>, transform: &Transform2D, color: Color) {
+    let (font_size, font_scale, font_scale_aspect) = camera_font_scale(transform.scale.x);
+    let params = TextParams {
+        font,
+        font_size,
+        font_scale,
+        font_scale_aspect,
+        rotation: 0.,
+        color,
+    };
+
+    match text {
+        Text::Left(text) => {
+            draw_text_ex(text, transform.position.re, transform.position.im, params);
+        }
+        Text::Center(text) => {
+            let dimension = measure_text(text.as_ref(), font, font_size, font_scale);
+            draw_text_ex(
+                text,
+                transform.position.re - dimension.width / 3.5,
+                transform.position.im,
+                params,
+            );
+        }
+        Text::Right(_text) => unimplemented!("I'm lazy implementing text right"),
+    }
+}
+
+/// Keep every command instead of drawing it, so tests can check what would
+/// have been drawn without a GPU.
+#[cfg(test)]
+#[derive(Default)]
+pub struct RecordingBackend {
+    pub commands: Vec<(Layer, DrawCommand)>,
+}
+
+#[cfg(test)]
+impl RenderBackend for RecordingBackend {
+    fn draw(&mut self, layer: Layer, command: DrawCommand) {
+        self.commands.push((layer, command));
+    }
+}
@@ -0,0 +1,83 @@
+use macroquad::{
+    models::{draw_mesh, Mesh, Vertex},
+    prelude::*,
+};
+
+use crate::{
+    components::{BlendMode, Transform2D},
+    konst::VIRTUAL_STAGE_ASPECT_RATIO,
+    math::ToVec2,
+    vec2,
+};
+
+// INFO : macroquad split draw call every 5000 indices, so keep each mesh under it
+const QUADS_PER_MESH: usize = 5000 / 6;
+
+const CORNERS: [Vec2; 4] = [
+    Vec2::new(-0.5, -0.5),
+    Vec2::new(0.5, -0.5),
+    Vec2::new(0.5, 0.5),
+    Vec2::new(-0.5, 0.5),
+];
+
+#[derive(Debug, Clone, Copy)]
+pub struct SpriteQuad {
+    pub source: Rect,
+    pub transform: Transform2D,
+    pub tint: Color,
+    pub blend: BlendMode,
+}
+
+/// Every sprite drawn on the stage in one frame, submitted as a few big mesh
+/// instead of one `draw_texture_ex` per sprite.
+#[derive(Default)]
+pub struct SpriteBatch {
+    quads: Vec<SpriteQuad>,
+}
+
+impl SpriteBatch {
+    pub fn push(&mut self, quad: SpriteQuad) {
+        self.quads.push(quad);
+    }
+
+    /// Draw every queued sprite with the currently active camera and empty the batch.
+    pub fn flush(&mut self, texture: &Texture2D) {
+        if self.quads.is_empty() {
+            return;
+        }
+
+        let texture_size = texture.size();
+        let mut mesh = Mesh {
+            vertices: Vec::with_capacity(QUADS_PER_MESH * 4),
+            indices: Vec::with_capacity(QUADS_PER_MESH * 6),
+            texture: Some(texture.clone()),
+        };
+
+        for chunk in self.quads.chunks(QUADS_PER_MESH) {
+            mesh.vertices.clear();
+            mesh.indices.clear();
+
+            // INFO : A chunk is at most `QUADS_PER_MESH * 4` vertices so their index fit in an u16
+            for (start, quad) in (0_u16..).step_by(CORNERS.len()).zip(chunk) {
+                let size = quad.transform.scale * VIRTUAL_STAGE_ASPECT_RATIO;
+                let center = quad.transform.position.to_vec2();
+                // INFO : Sprite are rotated the other way around, see `dir.rot()`
+                let rotation = Vec2::from_angle(-quad.transform.rotation);
+                let uv_origin = vec2!(quad.source.x, quad.source.y) / texture_size;
+                let uv_size = vec2!(quad.source.w, quad.source.h) / texture_size;
+
+                mesh.vertices.extend(CORNERS.iter().map(|corner| Vertex {
+                    position: (center + rotation.rotate(*corner * size)).extend(0.),
+                    uv: uv_origin + (*corner + 0.5) * uv_size,
+                    color: quad.tint,
+                }));
+                mesh.indices
+                    .extend([0, 1, 2, 0, 2, 3].iter().map(|index| start + index));
+            }
+
+            draw_mesh(&mesh);
+        }
+
+        self.quads.clear();
+    }
+}
@@ -0,0 +1,46 @@
+use macroquad::prelude::*;
+
+/// Camera over the 0..1 stage drawing into a render target. Rotation happen in
+/// pixel space, a `Camera2D` rotate in stage space and would squash the stage
+/// since it's taller than it is wide.
+pub struct StageCamera {
+    pub target: Vec2,
+    pub zoom: f32,
+    /// Rotation in degrees.
+    pub rotation: f32,
+    pub render_target: RenderTarget,
+}
+
+impl StageCamera {
+    pub const fn new(render_target: RenderTarget) -> Self {
+        Self {
+            target: vec2(0.5, 0.5),
+            zoom: 1.,
+            rotation: 0.,
+            render_target,
+        }
+    }
+}
+
+impl Camera for StageCamera {
+    fn matrix(&self) -> Mat4 {
+        let size = self.render_target.texture.size();
+
+        Mat4::from_scale(vec3(2. / size.x, 2. / size.y, 1.) * self.zoom)
+            * Mat4::from_rotation_z(self.rotation.to_radians())
+            * Mat4::from_scale(size.extend(1.))
+            * Mat4::from_translation(-self.target.extend(0.))
+    }
+
+    fn depth_enabled(&self) -> bool {
+        false
+    }
+
+    fn render_pass(&self) -> Option<RenderPass> {
+        Some(self.render_target.render_pass.clone())
+    }
+
+    fn viewport(&self) -> Option<(i32, i32, i32, i32)> {
+        None
+    }
+}
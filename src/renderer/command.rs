@@ -0,0 +1,102 @@
+use macroquad::prelude::*;
+
+use crate::{
+    components::{BlendMode, Layer, Text, Transform2D},
+    konst::VIRTUAL_STAGE_ASPECT_RATIO,
+    math::ToVec2,
+};
+
+use super::{RenderBackend, SpriteQuad};
+
+#[derive(Debug, Clone)]
+pub enum DrawCommand {
+    Sprite(SpriteQuad),
+    Text {
+        text: Text,
+        transform: Transform2D,
+        color: Color,
+    },
+    Rect {
+        rect: Rect,
+        color: Color,
+    },
+    Circle {
+        center: Vec2,
+        radius: f32,
+        color: Color,
+    },
+    /// Convex polygon filled as a triangle fan
+    #[cfg(debug_assertions)]
+    Polygon {
+        points: Vec<Vec2>,
+        color: Color,
+    },
+}
+
+/// Everything a frame want to draw in stage space, systems only push into it
+/// and a [`RenderBackend`] decide how it end up on the screen.
+#[derive(Default)]
+pub struct DrawList {
+    commands: Vec<(Layer, DrawCommand)>,
+}
+
+impl DrawList {
+    pub fn push(&mut self, layer: Layer, command: DrawCommand) {
+        self.commands.push((layer, command));
+    }
+
+    /// Queue the sprite, anything that can't be seen on the 0..1 stage is culled.
+    pub fn sprite(&mut self, layer: Layer, quad: SpriteQuad) {
+        let reach = (quad.transform.scale * VIRTUAL_STAGE_ASPECT_RATIO).length() / 2.;
+        let visible = Rect::new(-reach, -reach, 1. + reach * 2., 1. + reach * 2.);
+
+        if visible.contains(quad.transform.position.to_vec2()) {
+            self.push(layer, DrawCommand::Sprite(quad));
+        }
+    }
+
+    pub fn text(&mut self, layer: Layer, text: &Text, transform: &Transform2D, color: Color) {
+        self.push(
+            layer,
+            DrawCommand::Text {
+                text: text.clone(),
+                transform: *transform,
+                color,
+            },
+        );
+    }
+
+    pub fn rect(&mut self, layer: Layer, rect: Rect, color: Color) {
+        self.push(layer, DrawCommand::Rect { rect, color });
+    }
+
+    pub fn circle(&mut self, layer: Layer, center: Vec2, radius: f32, color: Color) {
+        self.push(
+            layer,
+            DrawCommand::Circle {
+                center,
+                radius,
+                color,
+            },
+        );
+    }
+
+    #[cfg(debug_assertions)]
+    pub fn polygon(&mut self, layer: Layer, points: Vec<Vec2>, color: Color) {
+        self.push(layer, DrawCommand::Polygon { points, color });
+    }
+
+    /// Hand every command to `backend` ordered by layer and empty the list,
+    /// sprites in the same layer are grouped by blend mode to keep them batched.
+    pub fn submit(&mut self, backend: &mut impl RenderBackend) {
+        self.commands.sort_by_key(|(layer, command)| match command {
+            DrawCommand::Sprite(quad) => (*layer, quad.blend),
+            _ => (*layer, BlendMode::Normal),
+        });
+
+        for (layer, command) in self.commands.drain(..) {
+            backend.draw(layer, command);
+        }
+        backend.flush();
+    }
+}
@@ -0,0 +1,136 @@
+use macroquad::prelude::*;
+
+use crate::components::Layer;
+
+use super::{DrawList, StageCamera};
+
+const MAX_SHAKE_OFFSET: f32 = 0.03;
+const MAX_SHAKE_ANGLE: f32 = 4.;
+const TRAUMA_DECAY: f32 = 1.2;
+
+/// What the player allowed the camera to do, shake and flashes can be turned off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EffectOptions {
+    pub shake: bool,
+    pub flash: bool,
+}
+
+impl Default for EffectOptions {
+    fn default() -> Self {
+        Self {
+            shake: true,
+            flash: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Pulse {
+    amount: f32,
+    duration: f32,
+    remaining: f32,
+}
+
+impl Pulse {
+    fn value(&self) -> f32 {
+        self.amount * self.remaining / self.duration
+    }
+}
+
+/// Time driven camera effects, every effect decay on its own so they can be
+/// triggered from gameplay without caring about what else is playing.
+#[derive(Debug, Default)]
+pub struct CameraEffects {
+    pub options: EffectOptions,
+    trauma: f32,
+    shake: Vec2,
+    shake_angle: f32,
+    zoom: Vec<Pulse>,
+    flash: Option<(Color, Pulse)>,
+    rotation_speed: f32,
+    rotation: f32,
+}
+
+impl CameraEffects {
+    /// Shake grow with the square of trauma, which goes from 0 to 1.
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0., 1.);
+    }
+
+    /// Zoom in by `amount` (0.1 is 10%) then ease back over `duration` seconds.
+    pub fn zoom_pulse(&mut self, amount: f32, duration: f32) {
+        self.zoom.push(Pulse {
+            amount,
+            duration,
+            remaining: duration,
+        });
+    }
+
+    /// Cover the stage with `color` and fade it out over `duration` seconds.
+    pub const fn flash(&mut self, color: Color, duration: f32) {
+        self.flash = Some((
+            color,
+            Pulse {
+                amount: color.a,
+                duration,
+                remaining: duration,
+            },
+        ));
+    }
+
+    /// Keep rotating the background by `speed` degree per second.
+    pub const fn set_background_rotation(&mut self, speed: f32) {
+        self.rotation_speed = speed;
+    }
+
+    pub fn update(&mut self, delta: f32) {
+        self.trauma = TRAUMA_DECAY.mul_add(-delta, self.trauma).max(0.);
+        let shake = if self.options.shake {
+            self.trauma * self.trauma
+        } else {
+            0.
+        };
+        self.shake =
+            vec2(rand::gen_range(-1., 1.), rand::gen_range(-1., 1.)) * shake * MAX_SHAKE_OFFSET;
+        self.shake_angle = rand::gen_range(-1., 1.) * shake * MAX_SHAKE_ANGLE;
+
+        for pulse in &mut self.zoom {
+            pulse.remaining -= delta;
+        }
+        self.zoom.retain(|pulse| pulse.remaining > 0.);
+
+        if let Some((_, pulse)) = &mut self.flash {
+            pulse.remaining -= delta;
+            if pulse.remaining <= 0. {
+                self.flash = None;
+            }
+        }
+
+        self.rotation = self.rotation_speed.mul_add(delta, self.rotation) % 360.;
+    }
+
+    pub fn apply(&self, camera: &mut StageCamera) {
+        camera.target = vec2(0.5, 0.5) + self.shake;
+        camera.zoom = 1. + self.zoom.iter().map(Pulse::value).sum::<f32>();
+        camera.rotation = self.shake_angle;
+    }
+
+    /// Same as [`CameraEffects::apply`] with the background rotation on top.
+    pub fn apply_background(&self, camera: &mut StageCamera) {
+        self.apply(camera);
+        camera.rotation += self.rotation;
+    }
+
+    pub fn draw(&self, commands: &mut DrawList) {
+        if let Some((color, pulse)) = self.flash.filter(|_| self.options.flash) {
+            commands.rect(
+                Layer::Effect,
+                Rect::new(-0.5, -0.5, 2., 2.),
+                Color {
+                    a: pulse.value(),
+                    ..color
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use macroquad::{
+    miniquad::{BlendFactor, BlendState, BlendValue, Equation, PipelineParams},
+    prelude::*,
+};
+
+// INFO : Same as macroquad default shader, only the blending is different
+const VERTEX: &str = r"#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    color = color0 / 255.0;
+    uv = texcoord;
+}";
+
+const FRAGMENT: &str = r"#version 100
+varying lowp vec4 color;
+varying lowp vec2 uv;
+
+uniform sampler2D Texture;
+
+void main() {
+    gl_FragColor = color * texture2D(Texture, uv);
+}";
+
+pub fn additive_material() -> Material {
+    let blend = BlendState::new(
+        Equation::Add,
+        BlendFactor::Value(BlendValue::SourceAlpha),
+        BlendFactor::One,
+    );
+
+    load_material(
+        ShaderSource::Glsl {
+            vertex: VERTEX,
+            fragment: FRAGMENT,
+        },
+        MaterialParams {
+            pipeline_params: PipelineParams {
+                color_blend: Some(blend),
+                alpha_blend: Some(blend),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )
+    .expect("Additive shader should compile")
+}
@@ -0,0 +1,188 @@
+use macroquad::prelude::*;
+
+use crate::{
+    konst::{
+        DESIRED_ASPECT_RATIO, PIXEL_STAGE_HEIGHT, PIXEL_STAGE_WIDTH, VIRTUAL_STAGE_HEIGHT,
+        VIRTUAL_STAGE_WIDTH,
+    },
+    resources::Resources,
+    utils::FPSCounter,
+    vec2,
+};
+
+mod backend;
+mod batch;
+mod camera;
+mod command;
+mod effects;
+mod material;
+mod scaling;
+
+#[cfg(test)]
+pub use backend::RecordingBackend;
+pub use backend::{MacroquadBackend, RenderBackend};
+pub use batch::{SpriteBatch, SpriteQuad};
+pub use camera::StageCamera;
+pub use command::{DrawCommand, DrawList};
+pub use effects::{CameraEffects, EffectOptions};
+pub use scaling::{DisplayOptions, ScaleMode};
+
+pub struct Renderer {
+    game: StageCamera,
+    background: StageCamera,
+    pub effects: CameraEffects,
+    display: DisplayOptions,
+    sprites: SpriteBatch,
+    additive: Material,
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        let game_render_target = stage_target(ScaleMode::default());
+
+        Self {
+            background: StageCamera::new(game_render_target.clone()),
+            game: StageCamera::new(game_render_target),
+            effects: CameraEffects::default(),
+            display: DisplayOptions::default(),
+            sprites: SpriteBatch::default(),
+            additive: material::additive_material(),
+        }
+    }
+}
+
+/// Render target shared by the game and background cameras, integer scaling
+/// get a low resolution one so it can be blown up by a whole factor.
+fn stage_target(scale: ScaleMode) -> RenderTarget {
+    let target = match scale {
+        ScaleMode::Integer => render_target(PIXEL_STAGE_WIDTH, PIXEL_STAGE_HEIGHT),
+        ScaleMode::Fit | ScaleMode::Stretch => {
+            render_target(VIRTUAL_STAGE_WIDTH, VIRTUAL_STAGE_HEIGHT)
+        }
+    };
+    target.texture.set_filter(FilterMode::Nearest);
+    target
+}
+
+impl Renderer {
+    /// Change how the stage is shown, the stage render target is recreated when
+    /// switching to or from integer scaling.
+    pub fn set_display(&mut self, display: DisplayOptions) {
+        let integer = |display: DisplayOptions| display.scale == ScaleMode::Integer;
+        if integer(display) != integer(self.display) {
+            let target = stage_target(display.scale);
+            self.background = StageCamera::new(target.clone());
+            self.game = StageCamera::new(target);
+        }
+        self.display = display;
+    }
+
+    /// Draw and empty `commands` on the stage.
+    pub fn submit(&mut self, commands: &mut DrawList, resources: &Resources) {
+        self.effects.draw(commands);
+        commands.submit(&mut MacroquadBackend::new(
+            &resources.sprite,
+            resources.font.as_ref(),
+            &mut self.sprites,
+            &self.additive,
+            (&self.game, &self.background),
+        ));
+    }
+
+    /// Switch to the stage camera, every stage drawing happen until [`Renderer::finalize`].
+    pub fn init(&mut self) {
+        self.effects.apply(&mut self.game);
+        self.effects.apply_background(&mut self.background);
+        set_camera(&self.game);
+
+        clear_background(BLACK);
+    }
+
+    /// Where the stage is drawn on the window, in window coordinates.
+    pub fn stage_rect(&self) -> Rect {
+        // INFO : Scale in physical pixels so integer scaling stay pixel perfect on high dpi
+        let dpi = screen_dpi_scale();
+        let physical = scaling::stage_rect(
+            self.display.scale,
+            vec2(screen_width(), screen_height()) * dpi,
+            self.game.render_target.texture.size(),
+        );
+
+        Rect::new(
+            physical.x / dpi,
+            physical.y / dpi,
+            physical.w / dpi,
+            physical.h / dpi,
+        )
+    }
+
+    pub fn finalize(&self, fps: &FPSCounter, resources: &Resources) {
+        // set_camera(&self.game);
+        // let re = Rect::new(0.02, 0.02, 0.9, 0.45);
+        // draw_rectangle(re.x, re.y, re.w, re.h, Color::new(1., 0., 0., 0.5));
+        //
+        // set_default_camera();
+
+        set_default_camera();
+
+        let width = screen_width();
+        let height = screen_height();
+        let texture = &self.game.render_target.texture;
+        let stage = self.stage_rect();
+
+        clear_background(Color::new(0.2, 0.2, 0.2, 1.));
+        if let Some(border) = resources.border.as_ref().filter(|_| self.display.border) {
+            draw_texture_ex(
+                border,
+                0.,
+                0.,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(vec2(width, height)),
+                    ..Default::default()
+                },
+            );
+        }
+        draw_texture_ex(
+            texture,
+            stage.x,
+            stage.y,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(stage.size()),
+                ..Default::default()
+            },
+        );
+        if self.display.scanlines {
+            scaling::draw_scanlines(stage, texture.height(), screen_dpi_scale());
+        }
+
+        fps.draw(resources.font.as_ref(), vec2!(width, height - 14.), 14.);
+    }
+}
+
+/// List every asset that got replaced by a placeholder, drawn on the whole window.
+pub fn draw_error_screen(resources: &Resources) {
+    set_default_camera();
+    clear_background(BLACK);
+
+    let params = |color| TextParams {
+        font: resources.font.as_ref(),
+        font_size: 12,
+        color,
+        ..Default::default()
+    };
+
+    draw_text_ex("Failed to load some assets", 20., 40., params(RED));
+    let mut y = 80.;
+    for error in &resources.errors {
+        draw_text_ex(&error.to_string(), 20., y, params(WHITE));
+        y += 24.;
+    }
+    draw_text_ex(
+        "Press Enter to continue with placeholders",
+        20.,
+        screen_height() - 30.,
+        params(GRAY),
+    );
+}
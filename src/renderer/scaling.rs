@@ -0,0 +1,98 @@
+use macroquad::prelude::*;
+
+use crate::utils::float_iter;
+
+const SCANLINE_SPACING: f32 = 2.;
+const SCANLINE_COLOR: Color = Color::new(0., 0., 0., 0.25);
+
+/// How the stage render target is fitted in the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScaleMode {
+    /// Low resolution stage blown up by a whole factor, so every stage pixel
+    /// keep the same size.
+    Integer,
+    /// As big as the window allow, the rest is letterboxed.
+    #[default]
+    Fit,
+    /// Fill the window, ignoring the aspect ratio.
+    Stretch,
+}
+
+impl ScaleMode {
+    pub const ALL: [Self; 3] = [Self::Integer, Self::Fit, Self::Stretch];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Integer => "integer",
+            Self::Fit => "fit",
+            Self::Stretch => "stretch",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.name().eq_ignore_ascii_case(name))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayOptions {
+    pub scale: ScaleMode,
+    pub scanlines: bool,
+    /// Draw `border.png` around the stage instead of a flat color.
+    pub border: bool,
+}
+
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        Self {
+            scale: ScaleMode::default(),
+            scanlines: false,
+            border: true,
+        }
+    }
+}
+
+/// Where a `stage` sized render target is drawn in a `screen` sized window,
+/// both in physical pixels.
+pub fn stage_rect(mode: ScaleMode, screen: Vec2, stage: Vec2) -> Rect {
+    let fit = (screen.x / stage.x).min(screen.y / stage.y);
+    let size = match mode {
+        ScaleMode::Integer if fit >= 1. => stage * fit.floor(),
+        // INFO : Only for windows smaller than the low resolution stage
+        ScaleMode::Integer => stage / fit.recip().ceil(),
+        ScaleMode::Fit => stage * fit,
+        ScaleMode::Stretch => screen,
+    };
+
+    let offset = ((screen - size) / 2.).floor();
+    Rect::new(offset.x, offset.y, size.x, size.y)
+}
+
+/// Darken the bottom of the stage texel `rows` drawn in `rect`, skipping rows
+/// when they are too thin for a line to show.
+pub fn draw_scanlines(rect: Rect, rows: f32, dpi: f32) {
+    let row = rect.h / rows;
+    let spacing = row * (SCANLINE_SPACING / (row * dpi)).ceil().max(1.);
+    let thickness = dpi.recip();
+
+    for y in float_iter(rect.y + spacing - thickness, rect.bottom(), spacing) {
+        draw_rectangle(rect.x, y, rect.w, thickness, SCANLINE_COLOR);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::konst::{PIXEL_STAGE_HEIGHT, PIXEL_STAGE_WIDTH};
+
+    #[test]
+    fn integer_scaling_blow_up_the_low_resolution_stage() {
+        let stage = vec2(PIXEL_STAGE_WIDTH as f32, PIXEL_STAGE_HEIGHT as f32);
+        let rect = stage_rect(ScaleMode::Integer, vec2(800., 600.), stage);
+
+        assert_eq!(rect.size(), stage * 2.);
+        assert_eq!(rect.point(), vec2(176., 26.));
+    }
+}
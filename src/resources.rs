@@ -1,20 +1,310 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
 use macroquad::prelude::*;
 
+use crate::{
+    atlas::SpriteAtlas,
+    audio::{parse_sounds, parse_tracks, SoundData, TrackData, TrackEntry, Wav},
+    pack,
+    stage::StageScript,
+};
+
+pub const ATLAS_TEXTURE: &str = "atlas.png";
+pub const ATLAS_METADATA: &str = "atlas.txt";
+const FONT: &str = "PressStart2P-Regular.ttf";
+const BORDER: &str = "border.png";
+const SOUNDS: &str = "sounds.txt";
+const MUSIC: &str = "music.txt";
+pub const STAGE_1: &str = "stage_1.txt";
+
+#[derive(Debug)]
+pub enum AssetError {
+    Missing { path: PathBuf },
+    Invalid { path: PathBuf, reason: String },
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing { path } => write!(f, "missing {}", path.display()),
+            Self::Invalid { path, reason } => write!(f, "invalid {}: {reason}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for AssetError {}
+
 pub struct Resources {
     pub sprite: Texture2D,
-    pub font: Font,
+    pub sprites: SpriteAtlas,
+    // INFO : `None` draw with macroquad built-in font
+    pub font: Option<Font>,
+    // INFO : Optional, the window is cleared to a flat color without it
+    pub border: Option<Texture2D>,
+    pub sounds: Vec<SoundData>,
+    pub music: Vec<TrackData>,
+    pub stage_1: StageScript,
+    /// Asset that failed to load and got replaced by a placeholder.
+    pub errors: Vec<AssetError>,
 }
 
 impl Resources {
     pub async fn new() -> Self {
-        let sprite = load_texture("./assets/atlas.png").await.unwrap();
-        sprite.set_filter(FilterMode::Nearest);
+        Self::load(&asset_root()).await
+    }
+
+    pub async fn load(root: &Path) -> Self {
+        let mut errors = Vec::new();
+
+        let atlas = match (
+            load_texture(root, ATLAS_TEXTURE).await,
+            load_atlas(root, ATLAS_METADATA).await,
+        ) {
+            (Ok(sprite), Ok(sprites)) => Some((sprite, sprites)),
+            (texture, sprites) => {
+                errors.extend(texture.err());
+                errors.extend(sprites.err().into_iter().flatten());
+                None
+            }
+        };
+        let (sprite, sprites) =
+            atlas.unwrap_or_else(|| (placeholder_texture(), SpriteAtlas::placeholder()));
+
+        let font = load_font(root, FONT)
+            .await
+            .map_err(|err| errors.push(err))
+            .ok();
+
+        let border = match load_texture(root, BORDER).await {
+            Ok(border) => Some(border),
+            Err(AssetError::Missing { .. }) => None,
+            Err(err) => {
+                errors.push(err);
+                None
+            }
+        };
 
-        let mut font = load_ttf_font("./assets/PressStart2P-Regular.ttf")
+        let sounds = load_sounds(root, SOUNDS, &mut errors).await;
+        let tracks = load_list(root, MUSIC, parse_tracks)
             .await
-            .unwrap();
-        font.set_filter(FilterMode::Nearest);
+            .unwrap_or_else(|err| {
+                errors.push(err);
+                Vec::new()
+            });
+        let music = load_music(root, &tracks, &mut errors).await;
+        let stage_1 = load_stage(root, STAGE_1, &tracks)
+            .await
+            .unwrap_or_else(|err| {
+                errors.push(err);
+                StageScript::default()
+            });
+
+        Self {
+            sprite,
+            sprites,
+            font,
+            border,
+            sounds,
+            music,
+            stage_1,
+            errors,
+        }
+    }
+}
+
+/// Directory holding the assets, in order of preference `--assets <dir>`,
+/// `MININVADERS_ASSETS`, `assets` next to the executable then `./assets`.
+pub fn asset_root() -> PathBuf {
+    let mut args = std::env::args();
+    if let Some(root) = args
+        .by_ref()
+        .find(|arg| arg == "--assets")
+        .and_then(|_| args.next())
+    {
+        return PathBuf::from(root);
+    }
+
+    if let Some(root) = std::env::var_os("MININVADERS_ASSETS") {
+        return PathBuf::from(root);
+    }
+
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| Some(exe.parent()?.join("assets")))
+        .filter(|root| root.is_dir())
+        .unwrap_or_else(|| PathBuf::from("./assets"))
+}
+
+/// Read `name` from the asset root, falling back to the embedded pack.
+async fn load_bytes(root: &Path, name: &str) -> Result<(PathBuf, Vec<u8>), AssetError> {
+    let path = root.join(name);
+
+    match load_file(&path.to_string_lossy()).await {
+        Ok(bytes) => Ok((path, bytes)),
+        Err(_) => pack::get(name).map_or(Err(AssetError::Missing { path }), |bytes| {
+            Ok((PathBuf::from(name), bytes.to_vec()))
+        }),
+    }
+}
+
+fn invalid(path: &Path, reason: &impl ToString) -> AssetError {
+    AssetError::Invalid {
+        path: path.to_path_buf(),
+        reason: reason.to_string(),
+    }
+}
+
+pub async fn load_texture(root: &Path, name: &str) -> Result<Texture2D, AssetError> {
+    let (path, bytes) = load_bytes(root, name).await?;
+    let image = Image::from_file_with_format(&bytes, None).map_err(|err| invalid(&path, &err))?;
+
+    let texture = Texture2D::from_image(&image);
+    texture.set_filter(FilterMode::Nearest);
+    Ok(texture)
+}
+
+/// An atlas without every [`crate::atlas::SPRITES`] is rejected, with one error per missing name.
+pub async fn load_atlas(root: &Path, name: &str) -> Result<SpriteAtlas, Vec<AssetError>> {
+    let (path, bytes) = load_bytes(root, name).await.map_err(|err| vec![err])?;
+    let source = String::from_utf8(bytes).map_err(|err| vec![invalid(&path, &err)])?;
+    let atlas = SpriteAtlas::parse(&source).map_err(|err| vec![invalid(&path, &err)])?;
+
+    let missing = atlas
+        .missing()
+        .map(|sprite| invalid(&path, &format!("no sprite named `{sprite}`")))
+        .collect::<Vec<_>>();
+    if missing.is_empty() {
+        Ok(atlas)
+    } else {
+        Err(missing)
+    }
+}
+
+pub async fn load_font(root: &Path, name: &str) -> Result<Font, AssetError> {
+    let (path, bytes) = load_bytes(root, name).await?;
+    let mut font = load_ttf_font_from_bytes(&bytes).map_err(|err| invalid(&path, &err))?;
+
+    font.set_filter(FilterMode::Nearest);
+    Ok(font)
+}
+
+/// Read and parse a metadata file listing other assets.
+async fn load_list<T>(
+    root: &Path,
+    name: &str,
+    parse: fn(&str) -> Result<Vec<T>, String>,
+) -> Result<Vec<T>, AssetError> {
+    let (path, bytes) = load_bytes(root, name).await?;
+    let source = String::from_utf8(bytes).map_err(|err| invalid(&path, &err))?;
+
+    parse(&source).map_err(|err| invalid(&path, &err))
+}
 
-        Self { sprite, font }
+/// Every sound listed in `name` that could be read, the other are pushed to `errors`.
+pub async fn load_sounds(root: &Path, name: &str, errors: &mut Vec<AssetError>) -> Vec<SoundData> {
+    let entries = load_list(root, name, parse_sounds)
+        .await
+        .unwrap_or_else(|err| {
+            errors.push(err);
+            Vec::new()
+        });
+
+    let mut sounds = Vec::new();
+    for entry in entries {
+        match load_bytes(root, &entry.file).await {
+            Ok((path, bytes)) => {
+                let Some(duration) = Wav::parse(&bytes).map(|wav| wav.duration()) else {
+                    errors.push(invalid(&path, &"not a wav file"));
+                    continue;
+                };
+                sounds.push(SoundData {
+                    name: entry.name,
+                    #[cfg(feature = "audio")]
+                    bytes,
+                    volume: entry.volume,
+                    instances: entry.instances,
+                    duration,
+                });
+            }
+            Err(err) => errors.push(err),
+        }
+    }
+
+    sounds
+}
+
+/// Every track of `entries` cut at its loop points, the other are pushed to `errors`.
+pub async fn load_music(
+    root: &Path,
+    entries: &[TrackEntry],
+    errors: &mut Vec<AssetError>,
+) -> Vec<TrackData> {
+    let mut tracks = Vec::new();
+    for entry in entries {
+        match load_bytes(root, &entry.file).await {
+            Ok((path, bytes)) => {
+                let Some(wav) = Wav::parse(&bytes) else {
+                    errors.push(invalid(&path, &"not a wav file"));
+                    continue;
+                };
+
+                let intro = entry.loop_start.min(wav.duration());
+                let loop_end = entry.loop_end.min(wav.duration());
+                if loop_end <= intro {
+                    errors.push(invalid(&path, &"the loop start after the end of the file"));
+                    continue;
+                }
+
+                tracks.push(TrackData {
+                    name: entry.name.clone(),
+                    title: entry.title.clone(),
+                    intro,
+                    #[cfg(feature = "audio")]
+                    sections: (wav.section(0., intro), wav.section(intro, loop_end)),
+                });
+            }
+            Err(err) => errors.push(err),
+        }
     }
+
+    tracks
+}
+
+/// The stage is rejected when it play a track that isn't in `tracks`.
+pub async fn load_stage(
+    root: &Path,
+    name: &str,
+    tracks: &[TrackEntry],
+) -> Result<StageScript, AssetError> {
+    let (path, bytes) = load_bytes(root, name).await?;
+    let source = String::from_utf8(bytes).map_err(|err| invalid(&path, &err))?;
+    let stage = StageScript::parse(&source).map_err(|err| invalid(&path, &err))?;
+
+    let tracks = tracks
+        .iter()
+        .map(|track| track.name.as_str())
+        .collect::<Vec<_>>();
+    stage
+        .check_music(&tracks)
+        .map_err(|err| invalid(&path, &err))?;
+    Ok(stage)
+}
+
+/// Magenta checkerboard, every sprite of [`SpriteAtlas::placeholder`] point to it.
+fn placeholder_texture() -> Texture2D {
+    let mut image = Image::gen_image_color(16, 16, BLACK);
+    for y in 0..16 {
+        for x in 0..16 {
+            if (x / 4 + y / 4) % 2 == 0 {
+                image.set_pixel(x, y, MAGENTA);
+            }
+        }
+    }
+
+    let texture = Texture2D::from_image(&image);
+    texture.set_filter(FilterMode::Nearest);
+    texture
 }
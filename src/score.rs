@@ -0,0 +1,47 @@
+#[cfg(debug_assertions)]
+const START_LIVES: u32 = 3;
+
+#[derive(Debug)]
+pub struct Score {
+    pub points: u64,
+    pub graze: u32,
+    // TODO : Lose them once the player can die and respawn, only the console change them for now
+    #[cfg(debug_assertions)]
+    pub lives: u32,
+}
+
+impl Default for Score {
+    fn default() -> Self {
+        Self {
+            points: 0,
+            graze: 0,
+            #[cfg(debug_assertions)]
+            lives: START_LIVES,
+        }
+    }
+}
+
+#[cfg(debug_assertions)]
+pub fn register_commands(registry: &mut crate::debug::CommandRegistry) {
+    use crate::debug::{arg, Command};
+
+    registry.register(Command {
+        name: "set",
+        usage: "set <lives|points|graze> <value>",
+        completions: &[&["lives", "points", "graze"]],
+        run: |game, args| {
+            let score = &mut game.score;
+            match args.first().copied() {
+                Some("lives") => score.lives = arg(args, 1, "lives")?,
+                Some("points") => score.points = arg(args, 1, "points")?,
+                Some("graze") => score.graze = arg(args, 1, "graze")?,
+                Some(other) => return Err(format!("unknown value `{other}`")),
+                None => return Err(String::from("missing value")),
+            }
+            Ok(format!(
+                "lives {} | points {} | graze {}",
+                score.lives, score.points, score.graze
+            ))
+        },
+    });
+}
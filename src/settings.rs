@@ -0,0 +1,358 @@
+use std::{
+    fmt::{self, Display},
+    path::PathBuf,
+};
+
+use macroquad::prelude::KeyCode;
+
+use crate::{
+    controls::{key_name, parse_key, Action, DEFAULT_BINDINGS},
+    renderer::{DisplayOptions, ScaleMode},
+};
+
+pub const SETTINGS_FILE: &str = "settings.cfg";
+
+const MIN_WINDOW_WIDTH: i32 = 320;
+const MIN_WINDOW_HEIGHT: i32 = 240;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Lunatic,
+}
+
+impl Difficulty {
+    pub const ALL: [Self; 4] = [Self::Easy, Self::Normal, Self::Hard, Self::Lunatic];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Easy => "easy",
+            Self::Normal => "normal",
+            Self::Hard => "hard",
+            Self::Lunatic => "lunatic",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name().eq_ignore_ascii_case(name))
+    }
+
+    /// How fast enemy bullets fly compared to normal.
+    pub const fn bullet_speed(self) -> f32 {
+        match self {
+            Self::Easy => 0.7,
+            Self::Normal => 1.,
+            Self::Hard => 1.25,
+            Self::Lunatic => 1.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowSettings {
+    pub width: i32,
+    pub height: i32,
+    pub fullscreen: bool,
+    pub vsync: bool,
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            height: 600,
+            fullscreen: false,
+            vsync: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Accessibility {
+    pub screen_shake: bool,
+    pub screen_flash: bool,
+    pub damage_numbers: bool,
+}
+
+impl Default for Accessibility {
+    fn default() -> Self {
+        Self {
+            screen_shake: true,
+            screen_flash: true,
+            damage_numbers: false,
+        }
+    }
+}
+
+/// Everything the player can change, saved as `key = value` lines.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub window: WindowSettings,
+    pub display: DisplayOptions,
+    pub sfx_volume: f32,
+    pub music_volume: f32,
+    pub difficulty: Difficulty,
+    pub accessibility: Accessibility,
+    pub bindings: Vec<(Action, KeyCode)>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            window: WindowSettings::default(),
+            display: DisplayOptions::default(),
+            sfx_volume: 1.,
+            music_volume: 0.8,
+            difficulty: Difficulty::default(),
+            accessibility: Accessibility::default(),
+            bindings: DEFAULT_BINDINGS.to_vec(),
+        }
+    }
+}
+
+impl Settings {
+    /// Read the settings file, writing the defaults when there is none yet.
+    /// Every line that couldn't be used is returned, those keep their default.
+    pub fn load() -> (Self, Vec<String>) {
+        let path = settings_path();
+        match std::fs::read_to_string(&path) {
+            Ok(source) => Self::parse(&source),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                let settings = Self::default();
+                let warnings = settings
+                    .save()
+                    .err()
+                    .map(|err| format!("{}: {err}", path.display()))
+                    .into_iter()
+                    .collect();
+                (settings, warnings)
+            }
+            Err(err) => (Self::default(), vec![format!("{}: {err}", path.display())]),
+        }
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = settings_path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, self.to_string())
+    }
+
+    pub fn parse(source: &str) -> (Self, Vec<String>) {
+        let mut settings = Self::default();
+        let mut warnings = Vec::new();
+
+        for (number, line) in source.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                warnings.push(format!("line {}: expected `key = value`", number + 1));
+                continue;
+            };
+
+            if let Err(err) = settings.set(key.trim(), value.trim()) {
+                warnings.push(format!("line {}: {err}", number + 1));
+            }
+        }
+
+        for (action, key) in &settings.bindings {
+            if let Some((other, _)) = settings
+                .bindings
+                .iter()
+                .find(|(other, other_key)| other != action && other_key == key)
+            {
+                warnings.push(format!(
+                    "`{}` is bound to both `{}` and `{}`",
+                    key_name(*key),
+                    action.name(),
+                    other.name()
+                ));
+                break;
+            }
+        }
+
+        (settings, warnings)
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "window_width" => self.window.width = parse_size(value, MIN_WINDOW_WIDTH)?,
+            "window_height" => self.window.height = parse_size(value, MIN_WINDOW_HEIGHT)?,
+            "fullscreen" => self.window.fullscreen = parse_bool(value)?,
+            "vsync" => self.window.vsync = parse_bool(value)?,
+            "scale_mode" => {
+                self.display.scale = ScaleMode::parse(value)
+                    .ok_or_else(|| format!("unknown scale mode `{value}`"))?;
+            }
+            "scanlines" => self.display.scanlines = parse_bool(value)?,
+            "border" => self.display.border = parse_bool(value)?,
+            "sfx_volume" => self.sfx_volume = parse_volume(value)?,
+            "music_volume" => self.music_volume = parse_volume(value)?,
+            "difficulty" => {
+                self.difficulty = Difficulty::parse(value)
+                    .ok_or_else(|| format!("unknown difficulty `{value}`"))?;
+            }
+            "screen_shake" => self.accessibility.screen_shake = parse_bool(value)?,
+            "screen_flash" => self.accessibility.screen_flash = parse_bool(value)?,
+            "damage_numbers" => self.accessibility.damage_numbers = parse_bool(value)?,
+            _ => {
+                let action = key
+                    .strip_prefix("key_")
+                    .and_then(|name| {
+                        DEFAULT_BINDINGS
+                            .into_iter()
+                            .map(|(action, _)| action)
+                            .find(|action| action.name() == name)
+                    })
+                    .ok_or_else(|| format!("unknown setting `{key}`"))?;
+                let key = parse_key(value).ok_or_else(|| format!("unknown key `{value}`"))?;
+                self.bind(action, key);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn key(&self, action: Action) -> Option<KeyCode> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map(|(_, key)| *key)
+    }
+
+    pub fn bind(&mut self, action: Action, key: KeyCode) {
+        match self.bindings.iter_mut().find(|(bound, _)| *bound == action) {
+            Some((_, bound)) => *bound = key,
+            None => self.bindings.push((action, key)),
+        }
+    }
+
+    /// Bind `key` to `action`, an other action already on `key` get the old
+    /// key of `action` so every action keep its own key.
+    pub fn rebind(&mut self, action: Action, key: KeyCode) {
+        let previous = self.key(action);
+        let taken = self
+            .bindings
+            .iter_mut()
+            .find(|(other, bound)| *other != action && *bound == key);
+
+        match (taken, previous) {
+            (Some((_, bound)), Some(previous)) => *bound = previous,
+            // INFO : Nothing to swap with, the other action can't be left without a key
+            (Some(_), None) => return,
+            (None, _) => {}
+        }
+        self.bind(action, key);
+    }
+}
+
+impl Display for Settings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "# Mininvader settings, lines that can't be read keep their default"
+        )?;
+        writeln!(f)?;
+        writeln!(f, "window_width = {}", self.window.width)?;
+        writeln!(f, "window_height = {}", self.window.height)?;
+        writeln!(f, "fullscreen = {}", self.window.fullscreen)?;
+        writeln!(f, "vsync = {}", self.window.vsync)?;
+        writeln!(f, "# integer, fit or stretch")?;
+        writeln!(f, "scale_mode = {}", self.display.scale.name())?;
+        writeln!(f, "scanlines = {}", self.display.scanlines)?;
+        writeln!(f, "border = {}", self.display.border)?;
+        writeln!(f)?;
+        writeln!(f, "# From 0 to 1")?;
+        writeln!(f, "sfx_volume = {}", self.sfx_volume)?;
+        writeln!(f, "music_volume = {}", self.music_volume)?;
+        writeln!(f)?;
+        writeln!(f, "# easy, normal, hard or lunatic")?;
+        writeln!(f, "difficulty = {}", self.difficulty.name())?;
+        writeln!(f)?;
+        writeln!(f, "screen_shake = {}", self.accessibility.screen_shake)?;
+        writeln!(f, "screen_flash = {}", self.accessibility.screen_flash)?;
+        writeln!(f, "damage_numbers = {}", self.accessibility.damage_numbers)?;
+        writeln!(f)?;
+        for (action, key) in &self.bindings {
+            writeln!(f, "key_{} = {}", action.name(), key_name(*key))?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "true" | "on" | "yes" => Ok(true),
+        "false" | "off" | "no" => Ok(false),
+        _ => Err(format!("expected `true` or `false`, got `{value}`")),
+    }
+}
+
+fn parse_volume(value: &str) -> Result<f32, String> {
+    let volume = value.parse::<f32>().map_err(|err| err.to_string())?;
+    if !(0. ..=1.).contains(&volume) {
+        return Err(format!("volume has to be between 0 and 1, got `{value}`"));
+    }
+    Ok(volume)
+}
+
+fn parse_size(value: &str, min: i32) -> Result<i32, String> {
+    let size = value.parse::<i32>().map_err(|err| err.to_string())?;
+    if size < min {
+        return Err(format!("has to be at least {min}, got `{value}`"));
+    }
+    Ok(size)
+}
+
+/// `--settings path`, `MININVADERS_SETTINGS`, then the user config directory,
+/// falling back to the working directory.
+pub fn settings_path() -> PathBuf {
+    let mut args = std::env::args();
+    if let Some(path) = args
+        .by_ref()
+        .find(|arg| arg == "--settings")
+        .and_then(|_| args.next())
+    {
+        return PathBuf::from(path);
+    }
+
+    if let Some(path) = std::env::var_os("MININVADERS_SETTINGS") {
+        return PathBuf::from(path);
+    }
+
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+        .map_or_else(
+            || PathBuf::from(SETTINGS_FILE),
+            |config| config.join("mininvader").join(SETTINGS_FILE),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use macroquad::prelude::KeyCode;
+
+    use super::Settings;
+    use crate::controls::Action;
+
+    #[test]
+    fn rebinding_a_taken_key_swap_them() {
+        let mut settings = Settings::default();
+        settings.rebind(Action::Accept, KeyCode::Up);
+
+        assert_eq!(settings.key(Action::Accept), Some(KeyCode::Up));
+        assert_eq!(settings.key(Action::Up), Some(KeyCode::Enter));
+        assert!(Settings::parse(&settings.to_string()).1.is_empty());
+    }
+}
@@ -3,6 +3,10 @@ use hecs::World;
 pub struct SpawnEvent {
     pub start: f32,
     pub is_spawned: bool,
+    /// Part of the stage the event set, like the music, only the last one is run when seeking.
+    // INFO : Seeking is a debug console command, release builds never read it
+    #[cfg_attr(not(debug_assertions), allow(dead_code))]
+    pub state: Option<&'static str>,
     pub action: Option<Box<dyn FnOnce(&mut World)>>,
 }
 
@@ -17,6 +21,22 @@ impl Spawner {
         self.lists.push(SpawnEvent {
             start,
             is_spawned: false,
+            state: None,
+            action: Some(Box::new(action)),
+        });
+    }
+
+    /// Same as [`Spawner::spawn`] for an event setting `state`, see [`SpawnEvent::state`].
+    pub fn spawn_state(
+        &mut self,
+        start: f32,
+        state: &'static str,
+        action: impl FnOnce(&mut World) + 'static,
+    ) {
+        self.lists.push(SpawnEvent {
+            start,
+            is_spawned: false,
+            state: Some(state),
             action: Some(Box::new(action)),
         });
     }
@@ -26,11 +46,135 @@ impl Spawner {
         self.lists
             .iter_mut()
             .filter(|event| !event.is_spawned && event.start < self.timer)
-            .for_each(|event| match event.action.take() {
-                Some(action) => (action)(world),
-                None => {}
+            .for_each(|event| {
+                event.is_spawned = true;
+                if let Some(action) = event.action.take() {
+                    (action)(world);
+                }
             });
 
         self.lists.retain(|event| !event.is_spawned);
     }
+
+    /// Jump to `target` seconds without replaying the stage, elapsed events are
+    /// dropped except the last one of every state which run in time order.
+    #[cfg(debug_assertions)]
+    pub fn seek(&mut self, world: &mut World, target: f32) {
+        let (mut elapsed, pending): (Vec<_>, Vec<_>) =
+            self.lists.drain(..).partition(|event| event.start < target);
+        self.lists = pending;
+        self.timer = target;
+
+        elapsed.sort_by(|a, b| a.start.total_cmp(&b.start));
+        let mut states = Vec::new();
+        let mut latest = Vec::new();
+        for event in elapsed.into_iter().rev() {
+            if let Some(state) = event.state.filter(|state| !states.contains(state)) {
+                states.push(state);
+                latest.push(event);
+            }
+        }
+
+        for mut event in latest.into_iter().rev() {
+            if let Some(action) = event.action.take() {
+                (action)(world);
+            }
+        }
+    }
+
+    /// Events not spawned yet and the time of the earliest one.
+    #[cfg(debug_assertions)]
+    pub fn pending(&self) -> (usize, Option<f32>) {
+        let next = self
+            .lists
+            .iter()
+            .map(|event| event.start)
+            .min_by(f32::total_cmp);
+        (self.lists.len(), next)
+    }
+}
+
+#[cfg(debug_assertions)]
+pub fn register_commands(registry: &mut crate::debug::CommandRegistry) {
+    use crate::debug::{arg, Command};
+
+    registry.register(Command {
+        name: "seek",
+        usage: "seek <seconds>",
+        completions: &[],
+        run: |game, args| {
+            let target: f32 = arg(args, 0, "seconds")?;
+            if target < 0. {
+                return Err(String::from("can't seek before the start"));
+            }
+            // INFO : Events can't be undone, going back restart the stage first
+            if target < game.spawner.timer {
+                game.restart_stage();
+            }
+
+            game.spawner.seek(&mut game.world, target);
+            Ok(format!("stage at {target:.1}s"))
+        },
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spawned_events_leave_the_list() {
+        let mut world = World::new();
+        let mut spawner = Spawner::default();
+        spawner.spawn(1., |world| {
+            world.spawn(());
+        });
+        spawner.spawn(3., |world| {
+            world.spawn(());
+        });
+
+        spawner.update(&mut world, 2.);
+        assert_eq!(world.len(), 1);
+        assert_eq!(spawner.lists.len(), 1);
+
+        spawner.update(&mut world, 2.);
+        assert_eq!(world.len(), 2);
+        assert!(spawner.lists.is_empty());
+    }
+
+    #[test]
+    fn seek_skip_spawns_and_keep_the_last_state() {
+        let mut world = World::new();
+        let mut spawner = Spawner::default();
+        spawner.spawn_state(0., "music", |world| {
+            world.spawn(("stage",));
+        });
+        spawner.spawn(1., |world| {
+            world.spawn((1_u32,));
+        });
+        spawner.spawn_state(2., "music", |world| {
+            world.spawn(("boss",));
+        });
+        spawner.spawn_state(2.5, "background", |world| {
+            world.spawn((2.5_f32,));
+        });
+        spawner.spawn(4., |world| {
+            world.spawn((4_u32,));
+        });
+
+        spawner.seek(&mut world, 3.);
+        assert!((spawner.timer - 3.).abs() < f32::EPSILON);
+        let music = world
+            .query_mut::<&&str>()
+            .into_iter()
+            .map(|(_, track)| *track)
+            .collect::<Vec<_>>();
+        assert_eq!(music, vec!["boss"]);
+        assert_eq!(world.query_mut::<&f32>().into_iter().count(), 1);
+        assert_eq!(world.query_mut::<&u32>().into_iter().count(), 0);
+
+        spawner.update(&mut world, 2.);
+        assert_eq!(world.query_mut::<&u32>().into_iter().count(), 1);
+        assert!(spawner.lists.is_empty());
+    }
 }
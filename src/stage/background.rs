@@ -0,0 +1,28 @@
+use macroquad::prelude::*;
+
+use crate::atlas::SpriteAtlas;
+use crate::components::{ParallaxBackground, ParallaxLayer};
+use crate::konst::VIRTUAL_STAGE_ASPECT_RATIO;
+use crate::vec2;
+
+// INFO : Keep the tile square on screen, the stage is taller than it is wide
+fn tile(width: f32) -> Vec2 {
+    vec2!(width, width * VIRTUAL_STAGE_ASPECT_RATIO)
+}
+
+pub fn space(sprites: &SpriteAtlas) -> ParallaxBackground {
+    ParallaxBackground::new(vec![
+        ParallaxLayer::new(sprites.get("bg_space"), tile(0.25), vec2!(0., 0.03)),
+        ParallaxLayer::new(sprites.get("bg_nebula"), tile(0.5), vec2!(0.01, 0.06))
+            .with_tint(Color::new(1., 1., 1., 0.6)),
+        ParallaxLayer::new(sprites.get("bg_stars"), tile(0.3), vec2!(0., 0.15)),
+    ])
+}
+
+pub fn boss_grid(sprites: &SpriteAtlas) -> ParallaxBackground {
+    ParallaxBackground::new(vec![
+        ParallaxLayer::new(sprites.get("bg_boss_grid"), tile(0.2), vec2!(0.02, 0.05)),
+        ParallaxLayer::new(sprites.get("bg_stars"), tile(0.3), vec2!(-0.03, 0.2))
+            .with_tint(Color::new(1., 0.6, 0.6, 0.8)),
+    ])
+}
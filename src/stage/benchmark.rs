@@ -0,0 +1,125 @@
+use hecs::World;
+use macroquad::prelude::*;
+use num_complex::Complex;
+
+use crate::atlas::SpriteAtlas;
+use crate::bullet::{BulletCollision, BulletPool};
+use crate::collision::{Collider, CollisionEvent, SpatialGrid};
+use crate::components::{
+    AttackMove, BulletSetup, Cooldown, Hitbox, Hitpoint, MoveParams, Moves, Transform2D,
+};
+use crate::entity::{create_enemy, create_player, stage_text};
+use crate::resources::Resources;
+use crate::system::collision;
+use crate::time::Timer;
+use crate::utils::float_iter;
+use crate::{cmpx, spawner::Spawner, vec2};
+
+const TURRETS: f32 = 24.;
+
+/// Keeps adding turrets that flood the stage with bullets to stress the
+/// bullet pool and collision pass, run the game with `--benchmark`.
+pub fn stage_benchmark(spawner: &mut Spawner, resources: &Resources) {
+    let sprites = &resources.sprites;
+    spawner.spawn_state(0., "player", create_player(sprites));
+    spawner.spawn(0., stage_text("Benchmark"));
+
+    for start in float_iter(0., TURRETS, 1.) {
+        let x = (start / TURRETS).mul_add(0.8, 0.1);
+        let y = (start % 4.).mul_add(0.1, 0.1);
+        spawner.spawn(start, create_turret(sprites, cmpx!(x, y), start));
+    }
+}
+
+fn create_turret(
+    sprites: &SpriteAtlas,
+    position: Complex<f32>,
+    rotation: f32,
+) -> impl FnOnce(&mut World) {
+    let sprite = sprites.get("red_alien");
+    let bullet = sprites.get("bullet_arrow_small");
+
+    move |world| {
+        create_enemy(
+            world,
+            Transform2D::new(position, vec2!(0.1), 0.),
+            sprite,
+            MoveParams::default(),
+            Moves::new(vec![]),
+            AttackMove::Circle {
+                sides: 64,
+                rotation,
+                rotation_per_fire: 0.,
+                cooldown: Cooldown::new(0.05),
+                setup: BulletSetup::new(bullet, Hitbox::circle(0.01)),
+            },
+            Hitbox::circle(0.03),
+            Hitpoint::invulnerable(),
+        );
+    }
+}
+
+/// Time the collision passes and every few seconds compare them against a
+/// single cell grid, which is the same as testing every pair.
+pub struct CollisionBenchmark {
+    brute_force: SpatialGrid,
+    report: Timer,
+    entities: f64,
+    bullets: f64,
+    frames: u32,
+}
+
+impl Default for CollisionBenchmark {
+    fn default() -> Self {
+        Self {
+            brute_force: SpatialGrid::new(1),
+            report: Timer::new(5., true),
+            entities: 0.,
+            bullets: 0.,
+            frames: 0,
+        }
+    }
+}
+
+impl CollisionBenchmark {
+    pub fn measure(
+        &mut self,
+        world: &World,
+        grid: &mut SpatialGrid,
+        bullets: &BulletPool,
+    ) -> (Vec<CollisionEvent>, Vec<BulletCollision>) {
+        let start = get_time();
+        let events = collision(world, grid);
+        let middle = get_time();
+        let bullet_events = bullets.collide(world, grid);
+        self.entities += middle - start;
+        self.bullets += get_time() - middle;
+        self.frames += 1;
+
+        self.report.update();
+        if self.report.completed() {
+            let start = get_time();
+            collision(world, &mut self.brute_force);
+            let middle = get_time();
+            bullets.collide(world, &mut self.brute_force);
+            let brute_force = (middle - start, get_time() - middle);
+
+            let frames = f64::from(self.frames);
+            println!(
+                "collision: {} colliders, grid {:.3} ms/frame, brute force {:.3} ms | {} bullets, grid {:.3} ms/frame, brute force {:.3} ms",
+                world.query::<&Collider>().iter().count(),
+                self.entities * 1000. / frames,
+                brute_force.0 * 1000.,
+                bullets.len(),
+                self.bullets * 1000. / frames,
+                brute_force.1 * 1000.,
+            );
+
+            self.entities = 0.;
+            self.bullets = 0.;
+            self.frames = 0;
+        }
+
+        (events, bullet_events)
+    }
+}
@@ -1,23 +1,16 @@
-use crate::entity::*;
-use crate::utils::float_iter;
-use crate::{cmpx, spawner::Spawner};
+mod background;
+mod benchmark;
+mod script;
 
-pub fn stage_1(spawner: &mut Spawner) {
-    spawner.spawn(0., create_player);
+use crate::resources::Resources;
+use crate::spawner::Spawner;
 
-    spawner.spawn(0., stage_text("Stage 1"));
+pub use benchmark::{stage_benchmark, CollisionBenchmark};
+pub use script::StageScript;
 
-    for start in float_iter(3., 6., 1.) {
-        spawner.spawn(start, create_red_alien_wait(cmpx!(0.5, -0.02)));
-    }
+/// Queue every spawn of a stage, called again whenever the stage restart.
+pub type Stage = fn(&mut Spawner, &Resources);
 
-    for start in float_iter(7., 9., 1.) {
-        spawner.spawn(start, create_red_alien(cmpx!(0.2, -0.02)));
-    }
-
-    for start in float_iter(10., 12., 1.) {
-        spawner.spawn(start, create_red_alien(cmpx!(0.8, -0.02)));
-    }
-
-    spawner.spawn(1., create_boss_1());
+pub fn stage_1(spawner: &mut Spawner, resources: &Resources) {
+    resources.stage_1.queue(spawner, &resources.sprites);
 }
@@ -0,0 +1,191 @@
+use hecs::World;
+use num_complex::Complex;
+
+use super::background;
+use crate::atlas::SpriteAtlas;
+use crate::cmpx;
+use crate::entity::{
+    background_speed, create_background, create_boss_1, create_player, create_red_alien,
+    create_red_alien_wait, crossfade_background, play_music, stage_text,
+};
+use crate::spawner::Spawner;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Backdrop {
+    Space,
+    BossGrid,
+}
+
+impl Backdrop {
+    fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "space" => Ok(Self::Space),
+            "boss_grid" => Ok(Self::BossGrid),
+            _ => Err(format!("unknown background `{name}`")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum StageEvent {
+    Player,
+    Background(Backdrop),
+    Crossfade(Backdrop, f32),
+    BackgroundSpeed(f32, f32),
+    Music(String, f32),
+    Text(String),
+    RedAlien(Complex<f32>),
+    RedAlienWait(Complex<f32>),
+    Boss1,
+}
+
+impl StageEvent {
+    fn parse(event: &str, args: &[&str]) -> Result<Self, String> {
+        let float = |index: usize| -> Result<f32, String> {
+            args.get(index)
+                .ok_or_else(|| format!("`{event}` is missing arguments"))?
+                .parse::<f32>()
+                .map_err(|err| err.to_string())
+        };
+
+        Ok(match event {
+            "player" => Self::Player,
+            "background" => Self::Background(Backdrop::parse(args.first().unwrap_or(&""))?),
+            "crossfade" => {
+                Self::Crossfade(Backdrop::parse(args.first().unwrap_or(&""))?, float(1)?)
+            }
+            "background_speed" => Self::BackgroundSpeed(float(0)?, float(1)?),
+            "music" => Self::Music(
+                args.first()
+                    .ok_or("`music` is missing a track")?
+                    .to_string(),
+                float(1)?,
+            ),
+            "text" => Self::Text(args.join(" ")),
+            "red_alien" => Self::RedAlien(cmpx!(float(0)?, float(1)?)),
+            "red_alien_wait" => Self::RedAlienWait(cmpx!(float(0)?, float(1)?)),
+            "boss_1" => Self::Boss1,
+            _ => return Err(format!("unknown event `{event}`")),
+        })
+    }
+
+    /// What part of the stage the event set, the other are one off spawns.
+    const fn state(&self) -> Option<&'static str> {
+        match self {
+            Self::Player => Some("player"),
+            Self::Background(_) | Self::Crossfade(..) => Some("background"),
+            Self::BackgroundSpeed(..) => Some("background_speed"),
+            Self::Music(..) => Some("music"),
+            Self::Text(_) | Self::RedAlien(_) | Self::RedAlienWait(_) | Self::Boss1 => None,
+        }
+    }
+
+    fn action(&self, sprites: &SpriteAtlas) -> Box<dyn FnOnce(&mut World)> {
+        let backdrop = |backdrop| match backdrop {
+            Backdrop::Space => background::space(sprites),
+            Backdrop::BossGrid => background::boss_grid(sprites),
+        };
+
+        match self {
+            Self::Player => Box::new(create_player(sprites)),
+            Self::Background(name) => Box::new(create_background(backdrop(*name))),
+            Self::Crossfade(name, duration) => {
+                Box::new(crossfade_background(backdrop(*name), *duration))
+            }
+            Self::BackgroundSpeed(speed, duration) => Box::new(background_speed(*speed, *duration)),
+            Self::Music(track, fade) => Box::new(play_music(track, *fade)),
+            Self::Text(text) => Box::new(stage_text(text)),
+            Self::RedAlien(position) => Box::new(create_red_alien(sprites, *position)),
+            Self::RedAlienWait(position) => Box::new(create_red_alien_wait(sprites, *position)),
+            Self::Boss1 => Box::new(create_boss_1(sprites)),
+        }
+    }
+}
+
+/// Spawns of a stage loaded from a file where every line is
+/// `time event arguments` and `#` start a comment, so it can be reloaded
+/// without a rebuild.
+#[derive(Debug, Clone, Default)]
+pub struct StageScript {
+    events: Vec<(f32, StageEvent)>,
+}
+
+impl StageScript {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut events = Vec::new();
+
+        for (number, line) in source.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let words = line.split_whitespace().collect::<Vec<_>>();
+            let [start, event, args @ ..] = &words[..] else {
+                return Err(format!(
+                    "line {}: expected `time event arguments`",
+                    number + 1
+                ));
+            };
+
+            let start = start
+                .parse::<f32>()
+                .map_err(|err| format!("line {}: {err}", number + 1))?;
+            let event = StageEvent::parse(event, args)
+                .map_err(|err| format!("line {}: {err}", number + 1))?;
+            events.push((start, event));
+        }
+
+        Ok(Self { events })
+    }
+
+    /// Error on the first `music` event playing a track that isn't one of `tracks`.
+    pub fn check_music(&self, tracks: &[&str]) -> Result<(), String> {
+        for (_, event) in &self.events {
+            if let StageEvent::Music(track, _) = event {
+                if !tracks.contains(&track.as_str()) {
+                    return Err(format!("`music` play `{track}` which is not in music.txt"));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn queue(&self, spawner: &mut Spawner, sprites: &SpriteAtlas) {
+        for (start, event) in &self.events {
+            match event.state() {
+                Some(state) => spawner.spawn_state(*start, state, event.action(sprites)),
+                None => spawner.spawn(*start, event.action(sprites)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_music_reject_unknown_tracks() {
+        let stage = StageScript::parse("0 music stage_1 0\n10 music boss_1 2").unwrap();
+
+        assert_eq!(stage.check_music(&["stage_1", "boss_1"]), Ok(()));
+        assert_eq!(
+            stage.check_music(&["stage_1"]),
+            Err("`music` play `boss_1` which is not in music.txt".to_string())
+        );
+    }
+
+    #[test]
+    fn shipped_stage_only_play_listed_tracks() {
+        let stage = StageScript::parse(include_str!("../../assets/stage_1.txt")).unwrap();
+        let tracks = crate::audio::parse_tracks(include_str!("../../assets/music.txt")).unwrap();
+        let names = tracks
+            .iter()
+            .map(|track| track.name.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(stage.check_music(&names), Ok(()));
+    }
+}
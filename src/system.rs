@@ -1,10 +1,19 @@
+use crate::animation::{AnimationState, Animator};
+use crate::atlas::SpriteAtlas;
+use crate::audio::Audio;
+use crate::bullet::{BulletCollision, BulletPool};
 use crate::cmpx;
+use crate::collision::{Collider, CollisionEvent, CollisionKind, CollisionLayer, SpatialGrid};
 use crate::components::*;
+use crate::cue::{Cue, Cues};
 use crate::entity::*;
+use crate::konst::VIRTUAL_STAGE_ASPECT_RATIO;
 use crate::math::*;
-use crate::renderer::Renderer;
-use crate::resources::Resources;
-use crate::utils::rand_dir;
+use crate::particle::{Effect, ParticlePool};
+use crate::renderer::{CameraEffects, DrawCommand, DrawList, SpriteQuad};
+use crate::score::Score;
+use crate::time::delta_time;
+use crate::utils::{float_iter, rand_dir};
 use crate::vec2;
 use crate::Action;
 use crate::Controls;
@@ -13,40 +22,294 @@ use hecs::World;
 use macroquad::prelude::*;
 use num_complex::Complex;
 
-pub fn draw_boss_hitpoint(world: &World, renderer: &Renderer) {
+pub fn draw_boss_hitpoint(world: &World, commands: &mut DrawList) {
     world
         .query::<(&Boss, &Hitpoint, &HealthBar)>()
         .iter()
         .for_each(|(_, (_, hp, healthbar))| {
-            renderer.draw_hp_bar(hp.hp, hp.max_hp, healthbar.0);
+            let bar = healthbar.0;
+            let width = bar.w * (hp.hp / hp.max_hp);
+            // INFO : Blink the bar to warn the boss is about to die
+            let color = if hp.is_low() && (get_time() * 4.).fract() < 0.5 {
+                WHITE
+            } else {
+                RED
+            };
+            commands.rect(Layer::Hud, Rect { w: width, ..bar }, color);
         });
 }
 
-pub fn draw_hitbox_system(world: &World, renderer: &Renderer) {
+#[cfg(debug_assertions)]
+pub fn draw_hitbox_system(world: &World, commands: &mut DrawList) {
     world
         .query::<(&Hitbox, &Transform2D)>()
         .iter()
         .for_each(|(_, (hitbox, transform))| {
-            renderer.debug_draw_hitbox(&transform, hitbox.radius);
+            commands.polygon(
+                Layer::Debug,
+                hitbox.shape.outline(transform, 24),
+                Color::new(1., 0., 0., 0.8),
+            );
+        });
+}
+
+// INFO : Cover past the stage so the corners stay filled while the background camera rotate
+const PARALLAX_MARGIN: f32 = 0.3;
+
+pub fn draw_parallax_system(world: &World, commands: &mut DrawList) {
+    world
+        .query::<(&ParallaxBackground, Option<&Tint>)>()
+        .iter()
+        .for_each(|(_, (background, tint))| {
+            let alpha = tint.map_or(1., |tint| tint.0.a);
+
+            for layer in &background.layers {
+                let scale = layer.tile / VIRTUAL_STAGE_ASPECT_RATIO;
+                let tint = Color {
+                    a: layer.tint.a * alpha,
+                    ..layer.tint
+                };
+
+                let start =
+                    layer.offset - layer.tile * ((PARALLAX_MARGIN / layer.tile).ceil() + 1.);
+                for y in float_iter(start.y, 1. + PARALLAX_MARGIN, layer.tile.y) {
+                    for x in float_iter(start.x, 1. + PARALLAX_MARGIN, layer.tile.x) {
+                        let center = vec2!(x, y) + layer.tile / 2.;
+                        commands.push(
+                            Layer::Background,
+                            DrawCommand::Sprite(SpriteQuad {
+                                source: layer.sprite.0,
+                                transform: Transform2D::new(center.to_cmpx(), scale, 0.),
+                                tint,
+                                blend: BlendMode::Normal,
+                            }),
+                        );
+                    }
+                }
+            }
+        });
+}
+
+pub fn draw_sprites_system(world: &World, commands: &mut DrawList) {
+    world
+        .query::<(
+            &Sprite,
+            &Transform2D,
+            Option<&Layer>,
+            Option<&Tint>,
+            Option<&BlendMode>,
+            Option<&HitFlash>,
+        )>()
+        .iter()
+        .for_each(|(_, (sprite, transform, layer, tint, blend, flash))| {
+            let layer = layer.copied().unwrap_or_default();
+            commands.sprite(
+                layer,
+                SpriteQuad {
+                    source: sprite.0,
+                    transform: *transform,
+                    tint: tint.map_or(WHITE, |tint| tint.0),
+                    blend: blend.copied().unwrap_or_default(),
+                },
+            );
+
+            // INFO : Same sprite added on top so it brighten toward the flash color
+            if let Some(flash) = flash {
+                commands.sprite(
+                    layer,
+                    SpriteQuad {
+                        source: sprite.0,
+                        transform: *transform,
+                        tint: Color {
+                            a: flash.color.a * flash.strength(),
+                            ..flash.color
+                        },
+                        blend: BlendMode::Additive,
+                    },
+                );
+            }
+        });
+}
+
+pub fn draw_bullets_system(bullets: &BulletPool, commands: &mut DrawList) {
+    bullets
+        .iter()
+        .for_each(|(_, transform, sprite, tint, blend)| {
+            commands.sprite(
+                Layer::EnemyBullet,
+                SpriteQuad {
+                    source: sprite.0,
+                    transform,
+                    tint,
+                    blend,
+                },
+            );
+        });
+}
+
+pub fn draw_particles_system(particles: &ParticlePool, commands: &mut DrawList) {
+    particles
+        .iter()
+        .for_each(|(transform, sprite, tint, blend)| {
+            commands.sprite(
+                Layer::Effect,
+                SpriteQuad {
+                    source: sprite.0,
+                    transform,
+                    tint,
+                    blend,
+                },
+            );
+        });
+}
+
+/// Turn every enemy bullet into a [`Effect::Cancel`] puff.
+pub fn cancel_bullets(bullets: &mut BulletPool, particles: &mut ParticlePool) {
+    for (_, transform, _, _, _) in bullets.iter() {
+        particles.emit(Effect::Cancel, transform.position);
+    }
+    bullets.clear();
+}
+
+pub fn draw_player_hitbox_system(world: &World, commands: &mut DrawList) {
+    world
+        .query::<(&Player, &Controllable, &Transform2D, &Hitbox)>()
+        .iter()
+        .for_each(|(_, (_, _, transform, hitbox))| {
+            let center = transform.position.to_vec2();
+            let radius = hitbox.shape.bounding_radius();
+            commands.circle(Layer::PlayerHitbox, center, radius, RED);
+            commands.circle(Layer::PlayerHitbox, center, radius * 0.6, WHITE);
         });
 }
 
-pub fn draw_sprites_system(world: &World, resources: &Resources, renderer: &Renderer) {
+pub fn draw_text_system(world: &World, commands: &mut DrawList) {
     world
-        .query::<(&Sprite, &Transform2D)>()
+        .query::<(&Text, &Transform2D, Option<&Tint>)>()
         .iter()
-        .for_each(|(_, (sprite, transform))| {
-            renderer.draw_sprite(&resources.sprite, sprite.0, transform)
+        .for_each(|(_, (text, transform, tint))| {
+            commands.text(
+                Layer::Hud,
+                text,
+                transform,
+                tint.map_or(WHITE, |tint| tint.0),
+            );
+        });
+}
+
+pub fn update_hit_flash(world: &mut World) {
+    let done = world
+        .query::<&mut HitFlash>()
+        .iter()
+        .filter_map(|(id, flash)| {
+            flash.update();
+            (flash.strength() <= 0.).then_some(id)
         })
+        .collect::<Vec<_>>();
+
+    for id in done {
+        let _ = world.remove_one::<HitFlash>(id);
+    }
+}
+
+pub fn update_fade(world: &World) {
+    world
+        .query::<(&mut Fade, &mut Tint)>()
+        .iter()
+        .for_each(|(_, (fade, tint))| {
+            tint.0.a = fade.update();
+        });
+}
+
+// INFO : Seconds the title of a new track stay on screen
+const NOW_PLAYING_TIME: f32 = 4.;
+
+// INFO : Horizontal speed where an entity start to bank
+const BANK_VELOCITY: f32 = 0.2;
+
+pub fn update_animation(world: &World, delta: f32) {
+    world
+        .query::<(&mut Animator, &mut Sprite, Option<&MoveParams>)>()
+        .without::<&Frozen>()
+        .iter()
+        .for_each(|(_, (animator, sprite, move_params))| {
+            let velocity = move_params.map_or(0., |move_params| move_params.velocity.re);
+            animator.set_state(if velocity < -BANK_VELOCITY {
+                AnimationState::BankLeft
+            } else if velocity > BANK_VELOCITY {
+                AnimationState::BankRight
+            } else {
+                AnimationState::Idle
+            });
+
+            if let Some(frame) = animator.update(delta) {
+                *sprite = frame;
+            }
+        });
 }
-pub fn draw_text_system(world: &World, resources: &Resources, renderer: &Renderer) {
+
+pub fn update_parallax(world: &World, delta: f32) {
     world
-        .query::<(&Text, &Transform2D)>()
+        .query::<&mut ParallaxBackground>()
         .iter()
-        .for_each(|(_, (text, transform))| renderer.draw_text(&text, &resources.font, transform))
+        .for_each(|(_, background)| background.update(delta));
 }
 
-pub fn player_controls(world: &mut World, controls: &Controls) {
+/// Despawn what faded out completely, old backgrounds and floating numbers.
+pub fn clean_faded(world: &mut World) {
+    let pending = world
+        .query::<(&Fade, &Tint)>()
+        .iter()
+        .filter(|(_, (fade, tint))| fade.is_done() && tint.0.a <= 0.)
+        .map(|(id, _)| id)
+        .collect::<Vec<_>>();
+
+    for id in pending {
+        let _ = world.despawn(id);
+    }
+}
+
+pub fn music_system(world: &mut World, audio: &mut Audio) {
+    let requests = world
+        .query::<&MusicRequest>()
+        .iter()
+        .map(|(id, request)| (id, request.clone()))
+        .collect::<Vec<_>>();
+
+    for (id, request) in requests {
+        match request {
+            MusicRequest::Play { track, fade } => audio.play_music(&track, fade),
+            MusicRequest::Stop { fade } => audio.stop_music(fade),
+        }
+        let _ = world.despawn(id);
+    }
+}
+
+pub fn draw_now_playing(now_playing: Option<(&str, f32)>, commands: &mut DrawList) {
+    // INFO : Show the title for a few seconds then fade it out
+    let Some((title, alpha)) =
+        now_playing.map(|(title, age)| (title, (NOW_PLAYING_TIME - age).clamp(0., 1.)))
+    else {
+        return;
+    };
+    if alpha <= 0. {
+        return;
+    }
+
+    commands.text(
+        Layer::Hud,
+        &Text::Left(format!("BGM: {title}")),
+        &Transform2D::new(cmpx!(0.03, 0.97), vec2!(0.025), 0.),
+        Color { a: alpha, ..WHITE },
+    );
+}
+
+pub fn player_controls(
+    world: &mut World,
+    controls: &Controls,
+    sprites: &SpriteAtlas,
+    cues: &mut Cues,
+) {
     let mut pending = Vec::new();
 
     world
@@ -94,10 +357,16 @@ pub fn player_controls(world: &mut World, controls: &Controls) {
 
             if controls.is_down(Action::Attack) && cooldown.0.completed() {
                 cooldown.0.update();
-                pending.push(create_player_generic_bullet(transform.position));
+                pending.push(create_player_generic_bullet(sprites, transform.position));
+                cues.push(Cue::PlayerShot, transform.position.re);
             } else {
                 cooldown.0.update();
             }
+
+            // TODO : Spend a bomb and cancel the bullets once the player has a stock of them
+            if controls.is_pressed(Action::Spell) {
+                cues.push(Cue::Bomb, transform.position.re);
+            }
         });
 
     for i in pending {
@@ -112,6 +381,7 @@ pub fn update_moves(world: &World) {
             Option<&mut Wanderable>,
             &Transform2D,
         )>()
+        .without::<&Frozen>()
         .iter()
         .for_each(|(_, (moves, move_params, wanderable, position))| {
             *move_params = moves.update(&move_params, position, wanderable);
@@ -119,14 +389,27 @@ pub fn update_moves(world: &World) {
 }
 pub fn update_movement(world: &World) {
     world
-        .query::<(&mut Transform2D, &mut MoveParams)>()
+        .query::<(&mut Transform2D, &mut MoveParams, Option<&mut FastMoving>)>()
+        .without::<&Frozen>()
         .iter()
-        .for_each(|(_, (transform, move_params))| {
-            move_params.update(&mut transform.position, get_frame_time());
+        .for_each(|(_, (transform, move_params, fast))| {
+            if let Some(fast) = fast {
+                fast.previous_position = transform.position;
+            }
+            move_params.update(&mut transform.position, delta_time());
         });
 }
 
-pub fn update_boss_move(world: &mut World) {
+// INFO : Degree per second the background turn while a boss is on stage
+const BOSS_BACKGROUND_ROTATION: f32 = 4.;
+
+pub fn update_boss_move(
+    world: &World,
+    bullets: &mut BulletPool,
+    particles: &mut ParticlePool,
+    effects: &mut CameraEffects,
+    bullet_speed: f32,
+) {
     let players = world
         .query::<(&Player, &Controllable, &Transform2D, &Hitbox)>()
         .iter()
@@ -135,6 +418,7 @@ pub fn update_boss_move(world: &mut World) {
 
     let mut boss = world
         .query::<(&Boss, &Enemy, &Transform2D, &BossMoves)>()
+        .without::<&Frozen>()
         .iter()
         .map(|(id, (_, _, transform, boss))| {
             (
@@ -151,7 +435,16 @@ pub fn update_boss_move(world: &mut World) {
     boss.iter_mut().for_each(|(id, transform, boss)| {
         if let Some(ref mut attack) = boss {
             if let Some(player) = players.first() {
-                handle_fire_bullet(world, &id, &attack.attack, transform, &player.1);
+                if handle_fire_bullet(
+                    bullets,
+                    id,
+                    &attack.attack,
+                    transform,
+                    &player.1,
+                    bullet_speed,
+                ) {
+                    trigger_attack_animation(world, *id);
+                }
 
                 update_cooldown_attack(&mut attack.attack);
                 let mut boss_move = world.get::<&mut BossMoves>(*id).unwrap();
@@ -178,6 +471,8 @@ pub fn update_boss_move(world: &mut World) {
 
             if attack_ref.timeout.completed() {
                 collection_attack_ref.0.pop_front();
+                effects.zoom_pulse(0.08, 0.4);
+                cancel_bullets(bullets, particles);
                 let mut boss_hp = world.get::<&mut Hitpoint>(*id).unwrap();
                 let hp = collection_attack_ref
                     .0
@@ -187,117 +482,321 @@ pub fn update_boss_move(world: &mut World) {
                 *boss_hp = hp
             }
         }
-    })
+    });
+
+    effects.set_background_rotation(if boss.is_empty() {
+        0.
+    } else {
+        BOSS_BACKGROUND_ROTATION
+    });
 }
 
-pub fn collision(world: &mut World) {
-    let players = world
-        .query::<(&Player, &Controllable, &Transform2D, &Hitbox)>()
-        .iter()
-        .map(|(id, (_, _, transform, hitbox))| (id.clone(), transform.clone(), hitbox.clone()))
-        .collect::<Vec<_>>();
+type ColliderRef<'a> = (
+    Entity,
+    &'a Collider,
+    &'a Transform2D,
+    &'a Hitbox,
+    Option<&'a FastMoving>,
+);
 
-    let enemies = world
-        .query::<(&Enemy, &Transform2D, &Hitbox)>()
-        .without::<&Bullet>()
-        .without::<&Boss>()
+pub fn collision(world: &World, grid: &mut SpatialGrid) -> Vec<CollisionEvent> {
+    let mut query = world.query::<(&Collider, &Transform2D, &Hitbox, Option<&FastMoving>)>();
+    let colliders = query
         .iter()
-        .map(|(id, (_, transform, hitbox))| (id.clone(), transform.clone(), hitbox.clone()))
-        .collect::<Vec<_>>();
+        .map(|(id, (collider, transform, hitbox, fast))| (id, collider, transform, hitbox, fast))
+        .collect::<Vec<ColliderRef>>();
 
-    let player_bullets = world
-        .query::<(&Player, &Bullet, &Transform2D, &Hitbox)>()
+    let bounds = colliders
         .iter()
-        .map(|(id, (_, _, transform, hitbox))| (id.clone(), transform.clone(), hitbox.clone()))
+        .map(|(_, _, transform, hitbox, fast)| {
+            let end = transform.position.to_vec2();
+            let start = fast.map_or(end, |fast| fast.previous_position.to_vec2());
+            (
+                (start + end) / 2.,
+                hitbox.shape.bounding_radius() + start.distance(end) / 2.,
+            )
+        })
         .collect::<Vec<_>>();
+    grid.rebuild(&bounds);
 
-    let enemy_bullets = world
-        .query::<(&Enemy, &Bullet, &Transform2D, &Hitbox)>()
-        .iter()
-        .map(|(id, (_, _, transform, hitbox))| (id.clone(), transform.clone(), hitbox.clone()))
-        .collect::<Vec<_>>();
+    let mut events = Vec::new();
+    let mut candidates = Vec::new();
+    for (index, current) in colliders.iter().enumerate() {
+        if current.1.mask == CollisionLayer::NONE && current.1.near_mask == CollisionLayer::NONE {
+            continue;
+        }
 
-    let boss = world
-        .query::<(&Boss, &Enemy, &Transform2D, &Hitbox)>()
+        let (position, radius) = bounds[index];
+        grid.query(position, radius + Hitbox::NEAR_MARGIN, &mut candidates);
+        for other in candidates.iter().map(|other| *other as usize) {
+            if other != index {
+                test_collision_pair(current, &colliders[other], &mut events);
+            }
+        }
+    }
+
+    // INFO : A bullet hitting something doesn't also graze it
+    let hits = events
         .iter()
-        .map(|(id, (_, _, transform, hitbox))| (id.clone(), transform.clone(), hitbox.clone()))
+        .filter(|event| event.kind == CollisionKind::Hit)
+        .map(|event| (event.entity, event.other))
         .collect::<Vec<_>>();
+    events.retain(|event| {
+        event.kind != CollisionKind::Near
+            || !hits.contains(&(event.entity, event.other))
+                && !hits.contains(&(event.other, event.entity))
+    });
+
+    events
+}
 
+fn test_collision_pair(
+    (entity, collider, transform, hitbox, fast): &ColliderRef,
+    (other, other_collider, other_transform, other_hitbox, other_fast): &ColliderRef,
+    events: &mut Vec<CollisionEvent>,
+) {
+    let is_hit = || match (fast, other_fast) {
+        (Some(fast), _) => hitbox.is_intersect_swept(
+            &Transform2D {
+                position: fast.previous_position,
+                ..**transform
+            },
+            transform,
+            other_transform,
+            other_hitbox,
+        ),
+        (None, Some(other_fast)) => other_hitbox.is_intersect_swept(
+            &Transform2D {
+                position: other_fast.previous_position,
+                ..**other_transform
+            },
+            other_transform,
+            transform,
+            hitbox,
+        ),
+        (None, None) => hitbox.is_intersect(transform, other_transform, other_hitbox),
+    };
+
+    if collider.mask.intersects(other_collider.layer) && is_hit() {
+        events.push(CollisionEvent {
+            entity: *entity,
+            other: *other,
+            kind: CollisionKind::Hit,
+        });
+    } else if collider.near_mask.intersects(other_collider.layer)
+        && hitbox.near(transform, other_transform, other_hitbox)
     {
-        if let Some(player) = players.first() {
-            for enemy_bullet in enemy_bullets {
-                if player
-                    .2
-                    .is_intersect(&player.1, &enemy_bullet.1, &enemy_bullet.2)
-                {
-                    let _ = world.despawn(enemy_bullet.0);
-                    // TODO : Reduce Player life
-                }
+        events.push(CollisionEvent {
+            entity: *entity,
+            other: *other,
+            kind: CollisionKind::Near,
+        });
+    }
+}
+
+pub fn damage_system(
+    world: &mut World,
+    events: &[CollisionEvent],
+    sprites: &SpriteAtlas,
+    particles: &mut ParticlePool,
+    effects: &mut CameraEffects,
+    cues: &mut Cues,
+    damage_numbers: bool,
+) {
+    let mut spent = Vec::new();
+    let mut drops = Vec::new();
+
+    for event in events.iter().filter(|e| e.kind == CollisionKind::Hit) {
+        if spent.contains(&event.entity) || spent.contains(&event.other) {
+            continue;
+        }
+
+        let Ok(damage) = world.get::<&Damage>(event.entity).map(|damage| damage.0) else {
+            continue;
+        };
+        spent.push(event.entity);
+
+        // TODO : Reduce Player life
+        let (is_dead, is_low, became_low) = world.get::<&mut Hitpoint>(event.other).map_or(
+            (false, false, false),
+            |mut hitpoint| {
+                let was_low = hitpoint.is_low();
+                hitpoint.damage(damage);
+                (
+                    hitpoint.is_dead(),
+                    hitpoint.is_low(),
+                    !was_low && hitpoint.is_low(),
+                )
+            },
+        );
+
+        if let Ok(mut animator) = world.get::<&mut Animator>(event.other) {
+            animator.trigger(AnimationState::Hit);
+        }
+
+        let is_enemy = world.get::<&Enemy>(event.other).is_ok();
+        let position = world
+            .get::<&Transform2D>(event.entity)
+            .map(|transform| transform.position);
+        if let (true, Ok(position)) = (is_enemy, position) {
+            particles.emit(Effect::Impact, position);
+            cues.push(Cue::EnemyHit, position.re);
+
+            if damage_numbers {
+                create_floating_number(&damage, position, WHITE)(world);
+            }
+        }
+
+        let flash = if is_low { RED } else { WHITE };
+        let _ = world.insert_one(event.other, HitFlash::new(flash, 0.08));
+
+        if became_low && world.get::<&Boss>(event.other).is_ok() {
+            let x = world
+                .get::<&Transform2D>(event.other)
+                .map_or(0.5, |transform| transform.position.re);
+            cues.push(Cue::BossLowHp, x);
+        }
+
+        if is_dead {
+            spent.push(event.other);
+
+            let is_boss = world.get::<&Boss>(event.other).is_ok();
+            if is_boss {
+                effects.add_trauma(0.8);
+                effects.flash(WHITE, 0.5);
+                // INFO : The boss is the end of the stage
+                world.spawn((MusicRequest::Stop { fade: 3. },));
+            }
+
+            if let (true, Ok(transform)) = (is_enemy, world.get::<&Transform2D>(event.other)) {
+                let effect = if is_boss {
+                    Effect::BossExplosion
+                } else {
+                    Effect::Explosion
+                };
+                particles.emit(effect, transform.position);
+                cues.push(Cue::EnemyDeath, transform.position.re);
+                drops.push(transform.position);
             }
         }
     }
 
-    {
-        for player_bullet in &player_bullets {
-            for boss in &boss {
-                if player_bullet
-                    .2
-                    .is_intersect(&player_bullet.1, &boss.1, &boss.2)
-                {
-                    let _ = world.despawn(player_bullet.0);
-
-                    // TODO : Make the damage based on bullet type
-                    match world.satisfies::<&Hitpoint>(boss.0) {
-                        Ok(exist) if exist => {
-                            world.get::<&mut Hitpoint>(boss.0).unwrap().damage(0.5);
-                        }
-                        _ => {}
-                    };
+    for entity in spent {
+        let _ = world.despawn(entity);
+    }
+    for position in drops {
+        create_point_item(sprites, position)(world);
+    }
+}
 
-                    let despawn = match world.get::<&Hitpoint>(boss.0) {
-                        Ok(hitpoint) if hitpoint.is_dead() => true,
-                        _ => false,
-                    };
+pub fn graze_system(
+    world: &mut World,
+    events: &[CollisionEvent],
+    score: &mut Score,
+    particles: &mut ParticlePool,
+    cues: &mut Cues,
+) {
+    for event in events.iter().filter(|e| e.kind == CollisionKind::Near) {
+        if world.get::<&Bullet>(event.other).is_err() || world.get::<&Grazed>(event.other).is_ok() {
+            continue;
+        }
 
-                    if despawn {
-                        let _ = world.despawn(boss.0);
-                    }
-                }
+        score.graze += 1;
+        if let Ok(transform) = world.get::<&Transform2D>(event.other) {
+            particles.emit(Effect::Graze, transform.position);
+            cues.push(Cue::Graze, transform.position.re);
+        }
+        let _ = world.insert_one(event.other, Grazed);
+    }
+}
+
+pub fn bullet_hit_system(
+    world: &mut World,
+    bullets: &mut BulletPool,
+    events: &[BulletCollision],
+    effects: &mut CameraEffects,
+    cues: &mut Cues,
+) {
+    let mut hit_players = Vec::new();
+
+    for event in events.iter().filter(|e| e.kind == CollisionKind::Hit) {
+        if !bullets.is_alive(event.bullet) || world.get::<&Invincible>(event.target).is_ok() {
+            continue;
+        }
+        bullets.kill(event.bullet);
+
+        // INFO : Bullets hitting the player in the same frame only count once
+        if world.get::<&Player>(event.target).is_ok() && !hit_players.contains(&event.target) {
+            hit_players.push(event.target);
+            effects.add_trauma(0.6);
+            effects.flash(Color::new(1., 0., 0., 0.4), 0.3);
+            if let Ok(transform) = world.get::<&Transform2D>(event.target) {
+                cues.push(Cue::PlayerDeath, transform.position.re);
             }
         }
+
+        if let Ok(mut hitpoint) = world.get::<&mut Hitpoint>(event.target) {
+            hitpoint.damage(BulletPool::DAMAGE);
+        }
+
+        if let Ok(mut animator) = world.get::<&mut Animator>(event.target) {
+            animator.trigger(AnimationState::Hit);
+        }
+
+        let _ = world.insert_one(event.target, HitFlash::new(RED, 0.15));
     }
+}
 
-    {
-        for player_bullet in player_bullets {
-            for enemy in &enemies {
-                if player_bullet
-                    .2
-                    .is_intersect(&player_bullet.1, &enemy.1, &enemy.2)
-                {
-                    let _ = world.despawn(player_bullet.0);
-
-                    // TODO : Make the damage based on bullet type
-                    match world.satisfies::<&Hitpoint>(enemy.0) {
-                        Ok(exist) if exist => {
-                            world.get::<&mut Hitpoint>(enemy.0).unwrap().damage(0.5);
-                        }
-                        _ => {}
-                    };
+pub fn bullet_graze_system(
+    bullets: &mut BulletPool,
+    events: &[BulletCollision],
+    score: &mut Score,
+    particles: &mut ParticlePool,
+    cues: &mut Cues,
+) {
+    for event in events.iter().filter(|e| e.kind == CollisionKind::Near) {
+        if bullets.is_alive(event.bullet) {
+            let position = bullets.transform(event.bullet).position;
+            score.graze += 1;
+            particles.emit(Effect::Graze, position);
+            cues.push(Cue::Graze, position.re);
+            bullets.set_grazed(event.bullet);
+        }
+    }
+}
 
-                    let despawn = match world.get::<&Hitpoint>(enemy.0) {
-                        Ok(hitpoint) if hitpoint.is_dead() => true,
-                        _ => false,
-                    };
+pub fn pickup_system(
+    world: &mut World,
+    events: &[CollisionEvent],
+    score: &mut Score,
+    cues: &mut Cues,
+    score_numbers: bool,
+) {
+    for event in events.iter().filter(|e| e.kind == CollisionKind::Hit) {
+        let Ok(item) = world.get::<&Item>(event.other).map(|item| *item) else {
+            continue;
+        };
 
-                    if despawn {
-                        let _ = world.despawn(enemy.0);
-                    }
+        let position = world
+            .get::<&Transform2D>(event.other)
+            .map_or(cmpx!(0.5), |transform| transform.position);
+        cues.push(Cue::Pickup, position.re);
+
+        match item {
+            Item::Point(points) => {
+                score.points += points;
+
+                if score_numbers {
+                    create_floating_number(&points, position, YELLOW)(world);
                 }
             }
         }
+        let _ = world.despawn(event.other);
     }
 }
-pub fn fire_bullets(world: &mut World) {
+
+/// `bullet_speed` scale every enemy bullet, it follow the difficulty.
+pub fn fire_bullets(world: &World, bullets: &mut BulletPool, bullet_speed: f32) {
     let player = world
         .query::<(&Player, &Controllable, &Transform2D)>()
         .iter()
@@ -306,13 +805,16 @@ pub fn fire_bullets(world: &mut World) {
 
     let pending = world
         .query::<(&AttackMove, &Transform2D)>()
+        .without::<&Frozen>()
         .iter()
         .map(|(id, (attack, transform))| (id.clone(), attack.clone(), transform.clone()))
         .collect::<Vec<_>>();
 
     if let Some(player) = player.first() {
         for (id, mut attack_move, transform) in pending {
-            handle_fire_bullet(world, &id, &attack_move, &transform, player);
+            if handle_fire_bullet(bullets, &id, &attack_move, &transform, player, bullet_speed) {
+                trigger_attack_animation(world, id);
+            }
 
             update_cooldown_attack(&mut attack_move);
             *(world.get::<&mut AttackMove>(id).unwrap()) = attack_move;
@@ -330,13 +832,21 @@ fn update_cooldown_attack(attack: &mut AttackMove) {
     };
 }
 
+fn trigger_attack_animation(world: &World, id: Entity) {
+    if let Ok(mut animator) = world.get::<&mut Animator>(id) {
+        animator.trigger(AnimationState::Attack);
+    }
+}
+
+/// Returns whether any bullet was fired.
 fn handle_fire_bullet(
-    world: &mut World,
+    bullets: &mut BulletPool,
     id: &Entity,
     attack_move: &AttackMove,
     transform: &Transform2D,
     player: &Transform2D,
-) {
+    bullet_speed: f32,
+) -> bool {
     match attack_move {
         AttackMove::AtPlayer {
             num,
@@ -347,44 +857,47 @@ fn handle_fire_bullet(
             setup,
         } if cooldown.0.completed() => {
             if *total_shoot <= 0 {
-                return;
+                return false;
             }
 
             if *num > 1 {
                 for i in 0..*num as i32 {
                     let angle = (i - 1) as f32 * spread;
-                    let dir =
-                        transform.position.dir(player.position()) * Complex::cdir(angle) * speed
-                            + (rand_dir() * 0.005).to_cmpx();
+                    let dir = transform.position.dir(player.position())
+                        * Complex::cdir(angle)
+                        * speed
+                        * bullet_speed
+                        + (rand_dir() * 0.005).to_cmpx();
                     let move_params = MoveParams::move_linear(dir);
                     let transform = Transform2D {
                         rotation: dir.rot(),
                         scale: vec2!(0.05),
                         ..*transform
                     };
-                    create_enemy_bullet(
-                        world,
-                        transform,
-                        setup.0.clone(),
-                        move_params,
-                        Hitbox::new(0.01),
-                    );
+                    create_enemy_bullet(bullets, transform, setup, move_params);
                 }
-                return;
+                return true;
             }
 
-            let dir = transform.position.dir(player.position()) * speed;
+            let dir = transform.position.dir(player.position()) * speed * bullet_speed;
             let move_params = MoveParams::move_linear(dir);
             let transform = Transform2D {
                 scale: vec2!(0.05),
                 rotation: dir.rot(),
                 ..*transform
             };
-            create_enemy_bullet(world, transform, setup.0, move_params, Hitbox::new(0.01));
+            create_enemy_bullet(bullets, transform, setup, move_params);
+            true
+        }
+        AttackMove::Multiple(moves) => {
+            // INFO : Every move has to fire, `any` would stop at the first one
+            let mut fired = false;
+            for attack_move in moves {
+                fired |=
+                    handle_fire_bullet(bullets, id, attack_move, transform, player, bullet_speed);
+            }
+            fired
         }
-        AttackMove::Multiple(moves) => moves
-            .iter()
-            .for_each(|attack_move| handle_fire_bullet(world, id, attack_move, transform, player)),
         AttackMove::Circle {
             sides,
             rotation_per_fire,
@@ -395,7 +908,7 @@ fn handle_fire_bullet(
             for side in 0..*sides {
                 let rotation =
                     (side as f32 / *sides as f32) * std::f32::consts::PI * 2. + *rotation;
-                let dir = Complex::cdir(rotation);
+                let dir = Complex::cdir(rotation) * bullet_speed;
                 let move_params = MoveParams::move_linear(dir);
 
                 let transform = Transform2D {
@@ -403,11 +916,12 @@ fn handle_fire_bullet(
                     rotation: dir.rot(),
                     ..*transform
                 };
-                create_enemy_bullet(world, transform, setup.0, move_params, Hitbox::new(0.01));
+                create_enemy_bullet(bullets, transform, setup, move_params);
             }
+            *sides > 0
         }
 
-        AttackMove::AtPlayer { .. } | AttackMove::Circle { .. } => {}
+        AttackMove::AtPlayer { .. } | AttackMove::Circle { .. } => false,
     }
 }
 
@@ -451,3 +965,257 @@ pub fn clean_offscreen(world: &mut World) {
         let _ = world.despawn(i);
     }
 }
+
+#[cfg(debug_assertions)]
+pub fn register_commands(registry: &mut crate::debug::CommandRegistry) {
+    use crate::debug::{toggle_arg, Command};
+
+    registry.register(Command {
+        name: "boss",
+        usage: "boss next_phase",
+        completions: &[&["next_phase"]],
+        run: |game, args| {
+            if args.first() != Some(&"next_phase") {
+                return Err(String::from("expected `next_phase`"));
+            }
+
+            let mut query = game.world.query::<&mut BossMoves>();
+            let phase = query
+                .iter()
+                .find_map(|(_, moves)| moves.0.front_mut())
+                .ok_or("no boss phase running")?;
+            // INFO : Ended like a timeout so `update_boss_move` cancel the bullets as usual
+            phase.timeout.stop();
+            Ok(String::from("skipping the boss phase"))
+        },
+    });
+
+    registry.register(Command {
+        name: "god",
+        usage: "god [on|off]",
+        completions: &[&["on", "off"]],
+        run: |game, args| {
+            let players = game
+                .world
+                .query::<(&Player, &Controllable)>()
+                .iter()
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>();
+            let current = players
+                .iter()
+                .any(|player| game.world.get::<&Invincible>(*player).is_ok());
+            let enabled = toggle_arg(args, current)?;
+
+            for player in players {
+                if enabled {
+                    let _ = game.world.insert_one(player, Invincible);
+                } else {
+                    let _ = game.world.remove_one::<Invincible>(player);
+                }
+            }
+            Ok(format!("god mode {}", if enabled { "on" } else { "off" }))
+        },
+    });
+
+    registry.register(Command {
+        name: "clear",
+        usage: "clear <bullets|enemies|particles>",
+        completions: &[&["bullets", "enemies", "particles"]],
+        run: |game, args| {
+            match args.first().copied() {
+                Some("bullets") => {
+                    cancel_bullets(&mut game.bullets, &mut game.particles);
+                    let player_bullets = game
+                        .world
+                        .query::<(&Player, &Bullet)>()
+                        .iter()
+                        .map(|(id, _)| id)
+                        .collect::<Vec<_>>();
+                    for bullet in player_bullets {
+                        let _ = game.world.despawn(bullet);
+                    }
+                }
+                Some("enemies") => {
+                    let enemies = game
+                        .world
+                        .query::<&Enemy>()
+                        .iter()
+                        .map(|(id, _)| id)
+                        .collect::<Vec<_>>();
+                    for enemy in enemies {
+                        let _ = game.world.despawn(enemy);
+                    }
+                }
+                Some("particles") => game.particles.clear(),
+                Some(other) => return Err(format!("can't clear `{other}`")),
+                None => return Err(String::from("missing what to clear")),
+            }
+            Ok(format!("cleared {}", args[0]))
+        },
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use hecs::World;
+    use macroquad::prelude::*;
+
+    use super::{bullet_hit_system, collision, damage_system, draw_boss_hitpoint, pickup_system};
+    use crate::{
+        atlas::SpriteAtlas,
+        bullet::{BulletCollision, BulletPool},
+        cmpx,
+        collision::{Collider, CollisionEvent, CollisionKind, CollisionLayer, SpatialGrid},
+        components::{
+            Boss, BulletSetup, Damage, Enemy, HealthBar, Hitbox, Hitpoint, Item, Layer, Player,
+            Sprite, Text, Tint, Transform2D,
+        },
+        cue::{Cue, Cues},
+        particle::{ParticleEffects, ParticlePool},
+        renderer::{CameraEffects, DrawCommand, DrawList, RecordingBackend},
+        score::Score,
+        vec2,
+    };
+
+    #[test]
+    fn boss_hitpoint_bar_follow_hp() {
+        let bar = Rect::new(0.04, 0.02, 0.9, 0.01);
+        let mut world = World::new();
+        world.spawn((
+            Boss,
+            Hitpoint {
+                hp: 5.,
+                ..Hitpoint::new(10.)
+            },
+            HealthBar(bar),
+        ));
+
+        let mut commands = DrawList::default();
+        draw_boss_hitpoint(&world, &mut commands);
+        let mut backend = RecordingBackend::default();
+        commands.submit(&mut backend);
+
+        let [(Layer::Hud, DrawCommand::Rect { rect, .. })] = backend.commands.as_slice() else {
+            panic!("expected a single hud rect, got {:?}", backend.commands);
+        };
+        assert!((rect.w - bar.w / 2.).abs() < f32::EPSILON);
+        assert!((rect.x - bar.x).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn bullet_hitting_the_player_does_not_graze() {
+        let mut world = World::new();
+        let transform = Transform2D::new(cmpx!(0.5), vec2!(0.01), 0.);
+        world.spawn((
+            Collider::new(CollisionLayer::PLAYER, CollisionLayer::ITEM)
+                .with_near(CollisionLayer::ENEMY_BULLET),
+            transform,
+            Hitbox::circle(0.01),
+        ));
+        world.spawn((
+            Collider::new(CollisionLayer::ENEMY_BULLET, CollisionLayer::PLAYER),
+            transform,
+            Hitbox::circle(0.01),
+        ));
+
+        let events = collision(&world, &mut SpatialGrid::new(8));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, CollisionKind::Hit);
+    }
+
+    #[test]
+    fn killed_enemy_drop_a_point_item() {
+        let mut world = World::new();
+        let position = cmpx!(0.5, 0.3);
+        let enemy = world.spawn((
+            Enemy,
+            Hitpoint::new(1.),
+            Transform2D::new(position, vec2!(0.05), 0.),
+        ));
+        let bullet = world.spawn((Damage(2.), Transform2D::new(position, vec2!(0.01), 0.)));
+
+        let sprites = SpriteAtlas::placeholder();
+        damage_system(
+            &mut world,
+            &[CollisionEvent {
+                entity: bullet,
+                other: enemy,
+                kind: CollisionKind::Hit,
+            }],
+            &sprites,
+            &mut ParticlePool::new(ParticleEffects::new(&sprites)),
+            &mut CameraEffects::default(),
+            &mut Cues::default(),
+            false,
+        );
+
+        assert!(!world.contains(enemy));
+        let items = world
+            .query_mut::<(&Item, &Transform2D)>()
+            .into_iter()
+            .map(|(_, (item, transform))| (*item, transform.position))
+            .collect::<Vec<_>>();
+        assert!(matches!(items[..], [(Item::Point(_), dropped)] if dropped == position));
+    }
+
+    #[test]
+    fn picked_point_item_show_its_score() {
+        let mut world = World::new();
+        let player = world.spawn(());
+        let item = world.spawn((
+            Item::Point(100),
+            Transform2D::new(cmpx!(0.5), vec2!(0.03), 0.),
+        ));
+        let events = [CollisionEvent {
+            entity: player,
+            other: item,
+            kind: CollisionKind::Hit,
+        }];
+
+        let mut score = Score::default();
+        pickup_system(&mut world, &events, &mut score, &mut Cues::default(), true);
+
+        assert_eq!(score.points, 100);
+        assert!(!world.contains(item));
+        let numbers = world
+            .query_mut::<(&Text, &Tint)>()
+            .into_iter()
+            .map(|(_, (text, tint))| (text.clone(), tint.0))
+            .collect::<Vec<_>>();
+        assert!(matches!(&numbers[..], [(Text::Center(text), YELLOW)] if text == "100"));
+    }
+
+    #[test]
+    fn player_react_once_to_bullets_hitting_in_the_same_frame() {
+        let mut world = World::new();
+        let transform = Transform2D::new(cmpx!(0.5), vec2!(0.01), 0.);
+        let player = world.spawn((Player, transform));
+
+        let mut bullets = BulletPool::default();
+        let setup = BulletSetup::new(Sprite::new(0., 0., 8., 8.), Hitbox::circle(0.01));
+        let events = [0., 0.]
+            .map(|_| bullets.spawn(transform, cmpx!(0.), &setup))
+            .map(|bullet| BulletCollision {
+                bullet,
+                target: player,
+                kind: CollisionKind::Hit,
+            });
+
+        let mut cues = Cues::default();
+        bullet_hit_system(
+            &mut world,
+            &mut bullets,
+            &events,
+            &mut CameraEffects::default(),
+            &mut cues,
+        );
+
+        assert_eq!(bullets.len(), 0);
+        assert_eq!(
+            cues.iter()
+                .filter(|(cue, _)| *cue == Cue::PlayerDeath)
+                .count(),
+            1
+        );
+    }
+}
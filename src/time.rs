@@ -1,5 +1,22 @@
+use std::cell::Cell;
+
 use macroquad::time::get_frame_time;
 
+thread_local! {
+    static TIME_SCALE: Cell<f32> = const { Cell::new(1.) };
+}
+
+/// Slow down or speed up every gameplay timer, 1 is real time.
+#[cfg(debug_assertions)]
+pub fn set_time_scale(scale: f32) {
+    TIME_SCALE.with(|time_scale| time_scale.set(scale.max(0.)));
+}
+
+/// Seconds since the last frame, scaled by [`set_time_scale`].
+pub fn delta_time() -> f32 {
+    get_frame_time() * TIME_SCALE.with(Cell::get)
+}
+
 #[derive(Debug, Clone)]
 pub struct Timer {
     pub time: f32,
@@ -39,7 +56,7 @@ impl Timer {
         if !self.repeating {
             self.completed = false;
         }
-        self.remaining_time -= get_frame_time();
+        self.remaining_time -= delta_time();
         if self.remaining_time <= 0. {
             self.completed = true;
         }
@@ -53,6 +70,12 @@ impl Timer {
         self.completed
     }
 
+    /// Seconds left before it complete.
+    #[cfg(debug_assertions)]
+    pub const fn remaining(&self) -> f32 {
+        self.remaining_time.max(0.)
+    }
+
     pub fn progress(&self) -> f32 {
         (self.remaining_time / self.time).max(0.)
     }
@@ -67,3 +90,22 @@ impl Timer {
         self.completed = true;
     }
 }
+
+#[cfg(debug_assertions)]
+pub fn register_commands(registry: &mut crate::debug::CommandRegistry) {
+    use crate::debug::{arg, Command};
+
+    registry.register(Command {
+        name: "timescale",
+        usage: "timescale <scale>",
+        completions: &[&["0.25", "0.5", "1", "2"]],
+        run: |_, args| {
+            let scale: f32 = arg(args, 0, "scale")?;
+            if !scale.is_finite() || scale < 0. {
+                return Err(String::from("the scale must be finite and not negative"));
+            }
+            set_time_scale(scale);
+            Ok(format!("time scale {scale}"))
+        },
+    });
+}
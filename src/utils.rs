@@ -34,17 +34,17 @@ impl FPSCounter {
 
         self.time_since_last_update = 0.;
     }
-    pub fn draw(&self, font: &Font, pos: Vec2, font_size: f32) {
+    pub fn draw(&self, font: Option<&Font>, pos: Vec2, font_size: f32) {
         let fps = format!("{}", self.fps.round());
         let (font_size, font_scale, font_scale_aspect) = camera_font_scale(font_size);
-        let len = measure_text(&fps, Some(font), font_size, font_scale);
+        let len = measure_text(&fps, font, font_size, font_scale);
         draw_text_ex(
             &fps,
             pos.x - len.width + 0.01,
             pos.y,
             TextParams {
                 color: WHITE,
-                font: Some(font),
+                font,
                 font_size,
                 font_scale,
                 font_scale_aspect,
@@ -62,25 +62,6 @@ pub fn rand_vec2(min: f32, max: f32) -> Vec2 {
     vec2(rand::gen_range(min, max), rand::gen_range(min, max)).normalize()
 }
 
-#[inline(always)]
-pub fn get_adjusted_screen(aspect_ratio: f32) -> Vec2 {
-    let (width, height, actual_aspect_ratio) = {
-        (
-            screen_width(),
-            screen_height(),
-            screen_width() / screen_height(),
-        )
-    };
-
-    let adjusted = if (actual_aspect_ratio * 10.).round() > (aspect_ratio * 10.).round() {
-        vec2(height * aspect_ratio, height)
-    } else {
-        vec2(width, width / aspect_ratio)
-    };
-
-    adjusted
-}
-
 pub fn float_iter(start: f32, end: f32, step: f32) -> impl Iterator<Item = f32> {
     std::iter::repeat(())
         .scan(start, move |value, _| {